@@ -1,20 +1,48 @@
+use std::fmt;
 use std::ops::BitAnd;
 use std::ops::BitOr;
 use std::collections::HashSet;
+use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::convert::TryInto;
 use regex::Regex;
 use rand::{seq::IteratorRandom, thread_rng};
 use rand::rngs::ThreadRng;
 use crate::color::Color;
 
+pub const CHESS960_POSITIONS: usize = 960;
+pub const CHESS960_STANDARD_POSITION: usize = 518;
+const MATE_SCORE: i32 = 10000;
+const CHESS960_KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
 lazy_static! {
-	static ref FORWARD_PAWN_MOVE: Regex = Regex::new("^([a-h])([1-8])$").unwrap();
-	static ref DISAMBIGUATED_PAWN_MOVE: Regex = Regex::new("^([a-h])([a-h])([1-8])$").unwrap();
+	static ref FORWARD_PAWN_MOVE: Regex = Regex::new("^([a-h])([1-8])(?:=?([NBRQ]))?$").unwrap();
+	static ref DISAMBIGUATED_PAWN_MOVE: Regex = Regex::new("^([a-h])x?([a-h])([1-8])(?:=?([NBRQ]))?$").unwrap();
 	static ref PIECE_MOVE: Regex = Regex::new("^([N,B,R,Q,K])([a-h])([1-8])$").unwrap();
-	static ref DISAMBIGUATED_PIECE_MOVE: Regex = Regex::new("^([N,B,R,Q,K])([a-h,1-8])([a-h])([1-8])$").unwrap();
-	// TODO: Handle disambiguation of multiple queens e.g. Qa1b2
+	static ref UCI_MOVE: Regex = Regex::new("^([a-h][1-8])([a-h][1-8])([qrbnQRBN])?$").unwrap();
+	static ref DISAMBIGUATED_PIECE_MOVE: Regex = Regex::new("^([N,B,R,Q,K])([a-h])?([1-8])?([a-h])([1-8])$").unwrap();
+	static ref KNIGHT_ATTACKS: Vec<Bitboard> = Bitboard::precompute(|square| {
+		Bitboard::from_squares(Direction::knight_moves().into_iter().filter_map(|moves| square.get_relative(moves)).collect())
+	});
+	static ref KING_ATTACKS: Vec<Bitboard> = Bitboard::precompute(|square| {
+		Bitboard::from_squares(Direction::all().into_iter().filter_map(|d| square.get_adjacent(d)).collect())
+	});
+	static ref PAWN_ATTACKS: Vec<Vec<Bitboard>> = Side::all().into_iter().map(|side| Bitboard::precompute(|square| {
+		let (left, right) = Direction::get_forward_diagonals(side);
+		Bitboard::from_squares(vec![left, right].into_iter().filter_map(|d| square.get_adjacent(d)).collect())
+	})).collect();
+	static ref ZOBRIST: Zobrist = Zobrist::new();
+	static ref RAYS: Vec<Vec<Bitboard>> = Direction::all().into_iter().map(|direction| Bitboard::precompute(|square| {
+		let mut to_return = Bitboard::empty();
+		let mut next = square.get_adjacent(direction);
+		while let Some(s) = next {
+			to_return = to_return | Bitboard::square(s);
+			next = s.get_adjacent(direction);
+		}
+		to_return
+	})).collect();
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -27,12 +55,14 @@ pub struct Board {
 	rooks: Bitboard,
 	queens: Bitboard,
 	kings: Bitboard,
-	prior_move_white: Option<Move>,
-	prior_move_black: Option<Move>,
+	en_passant: Option<Square>,
 	castling_rights_white_kingside: bool,
 	castling_rights_white_queenside: bool,
 	castling_rights_black_kingside: bool,
 	castling_rights_black_queenside: bool,
+	castling_rook_files: [File; 4],
+	chess960: bool,
+	zobrist: u64,
 }
 
 impl Board {
@@ -46,7 +76,7 @@ impl Board {
 		let rooks = Bitboard::empty();
 		let queens = Bitboard::empty();
 		let kings = Bitboard::empty();
-		Self {
+		let mut to_return = Self {
 			white,
 			black,
 			pawns,
@@ -55,13 +85,17 @@ impl Board {
 			rooks,
 			queens,
 			kings,
-			prior_move_white: None,
-			prior_move_black: None,
+			en_passant: None,
 			castling_rights_white_kingside: true,
 			castling_rights_white_queenside: true,
 			castling_rights_black_kingside: true,
 			castling_rights_black_queenside: true,
-		}
+			castling_rook_files: Castle::get_standard_rook_files(),
+			chess960: false,
+			zobrist: 0,
+		};
+		to_return.zobrist = to_return.compute_zobrist();
+		return to_return;
 	}
 
 	pub fn starting_position() -> Self {
@@ -98,7 +132,7 @@ impl Board {
 			]
 		);
 
-		Self {
+		let mut to_return = Self {
 			white,
 			black,
 			pawns,
@@ -107,15 +141,118 @@ impl Board {
 			rooks,
 			bishops,
 			knights,
-			prior_move_white: None,
-			prior_move_black: None,
+			en_passant: None,
 			castling_rights_white_kingside: true,
 			castling_rights_white_queenside: true,
 			castling_rights_black_kingside: true,
 			castling_rights_black_queenside: true,
+			castling_rook_files: Castle::get_standard_rook_files(),
+			chess960: false,
+			zobrist: 0,
+		};
+		to_return.zobrist = to_return.compute_zobrist();
+		return to_return;
+	}
+
+	pub fn chess960_position(n: usize) -> Result<Self, String> {
+		if n >= CHESS960_POSITIONS {
+			return Err(format!("Chess960 starting positions are numbered 0 to {} but {} was given.", CHESS960_POSITIONS - 1, n));
+		}
+
+		let back_rank = Self::get_chess960_back_rank(n);
+		let mut to_return = Self::empty();
+		for (file, piece) in File::all().into_iter().zip(back_rank.iter()) {
+			to_return.add(Side::White, *piece, Square::new(file, Rank::One));
+			to_return.add(Side::White, Piece::Pawn, Square::new(file, Rank::Two));
+			to_return.add(Side::Black, Piece::Pawn, Square::new(file, Rank::Seven));
+			to_return.add(Side::Black, *piece, Square::new(file, Rank::Eight));
+		}
+
+		let rook_files: Vec<File> = File::all().into_iter().filter(|f| back_rank[*f as usize] == Piece::Rook).collect();
+		for side in Side::all() {
+			to_return.castling_rook_files[Castle::get_index(side, Castle::Queenside)] = rook_files[0];
+			to_return.castling_rook_files[Castle::get_index(side, Castle::Kingside)] = rook_files[1];
+		}
+		to_return.chess960 = n != CHESS960_STANDARD_POSITION;
+		return Ok(to_return);
+	}
+
+	pub fn get_chess960_number(&self) -> Option<usize> {
+		// A FEN can't tell a Chess960 start with standard castling from a standard game, so ignore the flag.
+		(0..CHESS960_POSITIONS).find(|n| {
+			let mut board = Self::chess960_position(*n).unwrap();
+			board.chess960 = self.chess960;
+			board == *self
+		})
+	}
+
+	fn get_chess960_back_rank(n: usize) -> Vec<Piece> {
+		let mut back_rank: Vec<Option<Piece>> = vec![None; 8];
+		back_rank[2*(n % 4) + 1] = Some(Piece::Bishop);
+		back_rank[2*((n / 4) % 4)] = Some(Piece::Bishop);
+
+		let place_on_nth_empty = |back_rank: &mut Vec<Option<Piece>>, i: usize, piece: Piece| {
+			let file = (0..8).filter(|f| back_rank[*f].is_none()).nth(i).unwrap();
+			back_rank[file] = Some(piece);
+		};
+		let n = n / 16;
+		place_on_nth_empty(&mut back_rank, n % 6, Piece::Queen);
+		let (first_knight, second_knight) = CHESS960_KNIGHTS[n / 6];
+		place_on_nth_empty(&mut back_rank, second_knight, Piece::Knight);
+		place_on_nth_empty(&mut back_rank, first_knight, Piece::Knight);
+		for piece in [Piece::Rook, Piece::King, Piece::Rook] {
+			place_on_nth_empty(&mut back_rank, 0, piece);
+		}
+		return back_rank.into_iter().map(|p| p.unwrap()).collect();
+	}
+
+	pub fn is_chess960(&self) -> bool {
+		self.chess960
+	}
+
+	fn has_chess960_castling(&self) -> bool {
+		Side::all().into_iter().any(|side| Castle::all().into_iter().any(|castle| {
+			let is_king_moved = self.get_back_rank_king_square(side).is_some_and(|s| s.0 != File::E);
+			let is_rook_moved = self.get_castle_rook_square(side, castle).0 != castle.get_rook_source(side).0;
+			self.get_castling_rights(side, castle) && (is_king_moved || is_rook_moved)
+		}))
+	}
+
+	fn get_back_rank_king_square(&self, side: Side) -> Option<Square> {
+		let kings = self.get_side_pieces_bitboard(side, Piece::King) & Bitboard::rank(Castle::get_rank(side));
+		kings.to_squares().first().copied()
+	}
+
+	fn get_castle_king_square(&self, side: Side) -> Square {
+		let standard = Square::new(File::E, Castle::get_rank(side));
+		match self.chess960 {
+			true => self.get_back_rank_king_square(side).unwrap_or(standard),
+			false => standard
+		}
+	}
+
+	fn get_castle_rook_square(&self, side: Side, castle: Castle) -> Square {
+		Square::new(self.castling_rook_files[Castle::get_index(side, castle)], Castle::get_rank(side))
+	}
+
+	pub fn get_castle_move(&self, side: Side, castle: Castle) -> Move {
+		let king_square = self.get_castle_king_square(side);
+		match self.chess960 {
+			true => Move::new(king_square, self.get_castle_rook_square(side, castle)),
+			false => Move::new(king_square, castle.get_king_destination(side))
 		}
 	}
 
+	fn get_castle_for_move(&self, side: Side, piece: Piece, m: Move) -> Option<Castle> {
+		if piece != Piece::King {
+			return None;
+		}
+		let rooks = self.get_side_pieces_bitboard(side, Piece::Rook);
+		return Castle::all().into_iter().find(|c| {
+			self.get_castling_rights(side, *c) && rooks.contains(self.get_castle_rook_square(side, *c)) && (m == self.get_castle_move(side, *c))
+		});
+	}
+
 	pub fn singleton(side: Side, piece: Piece, square: Square) -> Self {
 		let mut board = Self::empty();
 		board.add(side, piece, square);
@@ -168,6 +305,7 @@ impl Board {
 			Piece::King => {self.kings = self.kings | bb;},
 		};
 
+		self.zobrist ^= ZOBRIST.piece(side, piece, square);
 	}
 
 	pub fn is_occupied(&self, square: Square) -> bool {
@@ -178,6 +316,51 @@ impl Board {
 		Fen::new(&self, side, half_moves, full_moves).to_string()
 	}
 
+	pub fn shredder_fen(&self, side: Side, half_moves: usize, full_moves: usize) -> String {
+		let mut fen = Fen::new(self, side, half_moves, full_moves);
+		fen.castling = Fen::get_shredder_castling(self);
+		return fen.to_string();
+	}
+
+	pub fn from_fen(fen: &str) -> Result<(Self, Side, usize, usize), String> {
+		Fen::try_parse(fen)
+	}
+
+	pub fn get_zobrist_key(&self, side: Side) -> u64 {
+		let mut to_return = self.zobrist;
+		if side == Side::Black {
+			to_return ^= ZOBRIST.side;
+		}
+		// Positions only repeat if the same en passant captures are available, not merely the same target square.
+		if let Some(target) = self.en_passant {
+			let pawns = self.get_side_pieces_bitboard(side, Piece::Pawn).to_squares();
+			if !pawns.into_iter().any(|s| self.get_legal_moves(s).into_iter().any(|m| m.1 == target)) {
+				to_return ^= ZOBRIST.en_passant(target);
+			}
+		}
+		return to_return;
+	}
+
+	fn compute_zobrist(&self) -> u64 {
+		let mut to_return = 0;
+		for side in Side::all() {
+			for piece in Piece::all() {
+				for square in self.get_side_pieces(side, piece) {
+					to_return ^= ZOBRIST.piece(side, piece, square);
+				}
+			}
+			for castle in Castle::all() {
+				if self.get_castling_rights(side, castle) {
+					to_return ^= ZOBRIST.castling(side, castle);
+				}
+			}
+		}
+		if let Some(target) = self.en_passant {
+			to_return ^= ZOBRIST.en_passant(target);
+		}
+		return to_return;
+	}
+
 	pub fn get(&self, square: Square) -> Option<(Side, Piece)> {
 		match self.is_occupied(square) {
 			false => None,
@@ -215,21 +398,35 @@ impl Board {
 	}
 
 	pub fn is_checkmated(&self, side: Side) -> bool {
-		let kings = self.get_side_pieces_bitboard(side, Piece::King).to_squares();
-		assert_eq!(kings.len(), 1, "Checkmating is only supported for games with on king per side, but this game has: {:?}", kings);
-		let king = kings[0];
-		self.is_in_check(side) && self.has_no_legal_moves(king)
+		if self.get_side_pieces_bitboard(side, Piece::King).is_empty() {
+			return false;
+		}
+		self.is_in_check(side) && self.has_no_legal_moves(side)
 	}
 
 	pub fn is_stalemated(&self, side: Side) -> bool {
-		let kings = self.get_side_pieces_bitboard(side, Piece::King).to_squares();
-		assert_eq!(kings.len(), 1, "Stalemating is only supported for games with on king per side, but this game has: {:?}", kings);
-		let king = kings[0];
-		(!self.is_in_check(side)) && self.has_no_legal_moves(king)
+		if self.get_side_pieces_bitboard(side, Piece::King).is_empty() {
+			return false;
+		}
+		(!self.is_in_check(side)) && self.has_no_legal_moves(side)
+	}
+
+	pub fn is_insufficient_material(&self) -> bool {
+		if (self.pawns | self.rooks | self.queens).has_pieces() {
+			return false;
+		}
+		if (self.knights | self.bishops).count() <= 1 {
+			return true;
+		}
+		if self.knights.has_pieces() {
+			return false;
+		}
+		let colors: Vec<SquareColor> = self.bishops.to_squares().into_iter().map(|s| s.get_color()).collect();
+		return colors.iter().all(|c| *c == colors[0]);
 	}
 
-	fn has_no_legal_moves(&self, square: Square) -> bool {
-		self.get_legal_moves(square).len() == 0
+	fn has_no_legal_moves(&self, side: Side) -> bool {
+		self.get_side_squares(side).into_iter().all(|s| self.get_legal_moves(s).is_empty())
 	}
 
 	pub fn is_in_check(&self, side: Side) -> bool {
@@ -239,33 +436,93 @@ impl Board {
 	}
 
 	pub fn is_attacking(&self, attacker: Side, square: Square) -> bool {
-		
-		let immediate_diagonals = self.get_immediately_diagonal_and_backward_vision(attacker, square);
-		if (immediate_diagonals & self.get_side_pieces_bitboard(attacker, Piece::Pawn)).has_pieces() {
-			return true;
+		self.is_attacking_through(attacker, square, self.pieces(), self.get_side_bitboard(attacker))
+	}
+
+	fn is_attacking_through(&self, attacker: Side, square: Square, occupancy: Bitboard, attackers: Bitboard) -> bool {
+		(self.get_attackers_through(attacker, square, occupancy) & attackers).has_pieces()
+	}
+
+	fn get_attackers_through(&self, attacker: Side, square: Square, occupancy: Bitboard) -> Bitboard {
+		let defender = Side::get_opponent(attacker);
+		let pawns = Bitboard::pawn_attacks(defender, square) & self.pawns;
+		let kings = Bitboard::king_attacks(square) & self.kings;
+		let knights = Bitboard::knight_attacks(square) & self.knights;
+		return (pawns | kings | knights | self.get_slider_attackers(square, occupancy)) & self.get_side(attacker);
+	}
+
+	pub fn get_static_exchange_evaluation(&self, side: Side, square: Square) -> i32 {
+		let target = match self.get(square) {
+			Some((s, piece)) if s != side => piece,
+			_ => return 0
+		};
+
+		let mut attacker = self.get_least_valuable_attacker(side, square, self.pieces());
+		if attacker.is_none() {
+			return 0;
 		}
 
-		let adjacent = self.get_adjacent_vision(square);
-		if (adjacent & self.get_side_pieces_bitboard(attacker, Piece::King)).has_pieces() {
-			return true;
+		// gains[i] is the balance for whoever makes capture i, assuming the other side recaptures.
+		let mut gains = vec![target.get_value() as i32];
+		let mut occupancy = self.pieces();
+		let mut attacker_side = side;
+		while let Some((attacker_square, attacker_piece)) = attacker {
+			gains.push((attacker_piece.get_value() as i32) - gains[gains.len() - 1]);
+			occupancy = occupancy & Bitboard::square(attacker_square).get_inverse();
+			attacker_side = Side::get_opponent(attacker_side);
+			attacker = self.get_least_valuable_attacker(attacker_side, square, occupancy);
 		}
 
-		let knight_moves = self.get_knight_vision(square);
-		if (knight_moves & self.get_side_pieces_bitboard(attacker, Piece::Knight)).has_pieces() {
-			return true;
+		// Nobody was left to make the last recapture, and either side may stop capturing when it's behind.
+		gains.pop();
+		while gains.len() > 1 {
+			let gain = gains.pop().unwrap();
+			let last = gains.len() - 1;
+			gains[last] = -std::cmp::max(-gains[last], gain);
 		}
+		return gains[0];
+	}
 
-		let diagonals = self.get_diagonal_vision(square);
-		if (diagonals & self.get_side_pieces_bitboard(attacker, Piece::Bishop)).has_pieces() || (diagonals & self.get_side_pieces_bitboard(attacker, Piece::Queen)).has_pieces() {
-			return true;
+	fn get_least_valuable_attacker(&self, attacker: Side, square: Square, occupancy: Bitboard) -> Option<(Square, Piece)> {
+		let attackers = self.get_attackers_through(attacker, square, occupancy) & occupancy;
+		for piece in Piece::all() {
+			if let Some(s) = (attackers & self.get_pieces_bitboard(piece)).to_squares().first() {
+				return Some((*s, piece));
+			}
 		}
+		return None;
+	}
 
-		let laterals = self.get_lateral_vision(square);
-		if (laterals & self.get_side_pieces_bitboard(attacker, Piece::Rook)).has_pieces() || (laterals & self.get_side_pieces_bitboard(attacker, Piece::Queen)).has_pieces() {
-			return true;
+	pub fn get_hanging_pieces(&self, side: Side) -> Vec<Square> {
+		let opponent = Side::get_opponent(side);
+		return self.get_side_squares(side).into_iter()
+			.filter(|s| self.get(*s).unwrap().1 != Piece::King)
+			.filter(|s| self.get_static_exchange_evaluation(opponent, *s) > 0)
+			.collect();
+	}
+
+	fn leaves_king_in_check(&self, m: Move) -> bool {
+		let (side, piece) = match self.get(m.0) {
+			Some(p) => p,
+			_ => panic!("There's no piece on {:?}!", m.0)
+		};
+		let source = Bitboard::square(m.0);
+		let destination = Bitboard::square(m.1);
+		let mut captured = destination;
+		if (piece == Piece::Pawn) && (m.0.0 != m.1.0) && !self.is_occupied(m.1) {
+			if let Some(s) = m.1.get_adjacent(Direction::get_backward(side)) {
+				captured = captured | Bitboard::square(s);
+			}
 		}
 
-		return false;
+		let opponent = Side::get_opponent(side);
+		let occupancy = (self.pieces() & source.get_inverse() & captured.get_inverse()) | destination;
+		let attackers = self.get_side_bitboard(opponent) & captured.get_inverse();
+		let kings = match piece {
+			Piece::King => (self.get_side_pieces_bitboard(side, Piece::King) & source.get_inverse()) | destination,
+			_ => self.get_side_pieces_bitboard(side, Piece::King)
+		};
+		return kings.to_squares().into_iter().any(|k| self.is_attacking_through(opponent, k, occupancy, attackers));
 	}
 
 	pub fn has_vision(&self, from: Square, to: Square) -> bool {
@@ -294,40 +551,182 @@ impl Board {
 		return self.get_side_squares(Side::get_opponent(side)).into_iter().filter(|piece| self.has_vision(*piece, square)).count();
 	}
 
+	pub fn get_n_xray_defenders(&self, side: Side, square: Square) -> usize {
+		self.get_n_defenders(side, square) + self.get_n_hidden_sliders(side, square)
+	}
+
+	pub fn get_n_xray_attackers(&self, side: Side, square: Square) -> usize {
+		self.get_n_attackers(side, square) + self.get_n_hidden_sliders(Side::get_opponent(side), square)
+	}
+
+	fn get_n_hidden_sliders(&self, side: Side, square: Square) -> usize {
+		let sliders = (self.bishops | self.rooks | self.queens) & self.get_side(side);
+		let mut occupancy = self.pieces();
+		let mut visible = self.get_slider_attackers(square, occupancy) & sliders;
+		let mut to_return = 0;
+		while visible.has_pieces() {
+			occupancy = occupancy & visible.get_inverse();
+			visible = self.get_slider_attackers(square, occupancy) & sliders & occupancy;
+			to_return += visible.count();
+		}
+		return to_return;
+	}
+
+	fn get_slider_attackers(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+		let diagonals = Bitboard::diagonal_attacks(square, occupancy) & (self.bishops | self.queens);
+		let laterals = Bitboard::lateral_attacks(square, occupancy) & (self.rooks | self.queens);
+		return diagonals | laterals;
+	}
+
 	pub fn make_moves(&mut self, moves: Vec<Move>) {
 		for m in moves {
-			self.make_move(m)
+			self.make_move(m);
 		}
 	}
 
-	pub fn make_move(&mut self, m: Move) {
+	pub fn make_move(&mut self, m: Move) -> Undo {
 		assert!(self.is_legal_move(m), "{:?} isn't a legal move.", m);
-		self.force_make_move(m);
+		self.force_make_move(m)
 	}
 
-	fn force_make_move(&mut self, m: Move) {
+	fn force_make_move(&mut self, m: Move) -> Undo {
 		let source = m.0;
 		let (side, piece) = self.get(source).unwrap();
-		let kingside_castle = Castle::Kingside.get_king_move(side);
-		let queenside_castle = Castle::Queenside.get_king_move(side);
-		if (piece == Piece::King) && (m == kingside_castle) {
-			let king_move = m;
-			let rook_move = Castle::Kingside.get_rook_move(side);
-			self.transform(king_move);
-			self.transform(rook_move);
-		} else if (piece == Piece::King) && (m == queenside_castle) {
-			let king_move = m;
-			let rook_move = Castle::Queenside.get_rook_move(side);
-			self.transform(king_move);
-			self.transform(rook_move);
-		} else {
-			self.transform(m);
+		let castle = self.get_castle_for_move(side, piece, m);
+		let undo = Undo {
+			m,
+			side,
+			piece,
+			captured: self.get_captured(m),
+			castle,
+			en_passant: self.en_passant,
+			castling_rights: self.get_all_castling_rights(),
+		};
+
+		match castle {
+			Some(c) => {
+				let rook_square = self.get_castle_rook_square(side, c);
+				self.remove(source);
+				self.remove(rook_square);
+				self.add(side, Piece::King, c.get_king_destination(side));
+				self.add(side, Piece::Rook, c.get_rook_destination(side));
+			},
+			None => self.transform(m)
+		};
+
+		self.update_castling_rights(side, piece, m);
+		self.set_en_passant(match (piece, Rank::distance(source.1, m.1.1)) {
+			(Piece::Pawn, 2) => source.get_adjacent(Direction::get_forward(side)),
+			_ => None
+		});
+		return undo;
+	}
+
+	pub fn unmake_move(&mut self, undo: Undo) {
+		let m = undo.m;
+		match undo.castle {
+			Some(castle) => {
+				self.remove(castle.get_king_destination(undo.side));
+				self.remove(castle.get_rook_destination(undo.side));
+				self.add(undo.side, Piece::King, m.0);
+				self.add(undo.side, Piece::Rook, self.get_castle_rook_square(undo.side, castle));
+			},
+			None => {
+				self.remove(m.1);
+				self.add(undo.side, undo.piece, m.0);
+			}
+		};
+		if let Some((square, piece)) = undo.captured {
+			self.add(Side::get_opponent(undo.side), piece, square);
+		}
+		self.set_en_passant(undo.en_passant);
+		self.set_all_castling_rights(undo.castling_rights);
+	}
+
+	fn get_captured(&self, m: Move) -> Option<(Square, Piece)> {
+		if !self.is_capture(m) {
+			return None;
+		}
+		match self.get(m.1) {
+			Some((_, piece)) => Some((m.1, piece)),
+			None => m.1.get_adjacent(Direction::get_backward(self.get(m.0).unwrap().0)).map(|s| (s, Piece::Pawn))
+		}
+	}
+
+	fn remove(&mut self, square: Square) {
+		if let Some((side, piece)) = self.get(square) {
+			self.zobrist ^= ZOBRIST.piece(side, piece, square);
+		}
+		let inverse = Bitboard::square(square).get_inverse();
+		self.white = self.white & inverse;
+		self.black = self.black & inverse;
+		for piece in Piece::all() {
+			self.set_pieces_bitboard(piece, self.get_pieces_bitboard(piece) & inverse);
+		}
+	}
+
+	fn update_castling_rights(&mut self, mover: Side, piece: Piece, m: Move) {
+		for side in Side::all() {
+			for castle in Castle::all() {
+				let rook_source = self.get_castle_rook_square(side, castle);
+				let is_king_move = (piece == Piece::King) && (side == mover);
+				let is_rook_move_or_capture = (m.0 == rook_source) || (m.1 == rook_source);
+				if is_king_move || is_rook_move_or_capture {
+					self.set_castling_rights(side, castle, false);
+				}
+			}
 		}
 	}
 
+	pub fn get_castling_rights(&self, side: Side, castle: Castle) -> bool {
+		match (side, castle) {
+			(Side::White, Castle::Kingside) => self.castling_rights_white_kingside,
+			(Side::White, Castle::Queenside) => self.castling_rights_white_queenside,
+			(Side::Black, Castle::Kingside) => self.castling_rights_black_kingside,
+			(Side::Black, Castle::Queenside) => self.castling_rights_black_queenside,
+		}
+	}
+
+	fn set_castling_rights(&mut self, side: Side, castle: Castle, rights: bool) {
+		if self.get_castling_rights(side, castle) != rights {
+			self.zobrist ^= ZOBRIST.castling(side, castle);
+		}
+		match (side, castle) {
+			(Side::White, Castle::Kingside) => {self.castling_rights_white_kingside = rights},
+			(Side::White, Castle::Queenside) => {self.castling_rights_white_queenside = rights},
+			(Side::Black, Castle::Kingside) => {self.castling_rights_black_kingside = rights},
+			(Side::Black, Castle::Queenside) => {self.castling_rights_black_queenside = rights},
+		};
+	}
+
+	fn get_all_castling_rights(&self) -> [bool; 4] {
+		let mut to_return = [false; 4];
+		for side in Side::all() {
+			for castle in Castle::all() {
+				to_return[Castle::get_index(side, castle)] = self.get_castling_rights(side, castle);
+			}
+		}
+		return to_return;
+	}
+
+	fn set_all_castling_rights(&mut self, rights: [bool; 4]) {
+		for side in Side::all() {
+			for castle in Castle::all() {
+				self.set_castling_rights(side, castle, rights[Castle::get_index(side, castle)]);
+			}
+		}
+	}
+
+	fn set_en_passant(&mut self, en_passant: Option<Square>) {
+		for target in self.en_passant.iter().chain(en_passant.iter()) {
+			self.zobrist ^= ZOBRIST.en_passant(*target);
+		}
+		self.en_passant = en_passant;
+	}
+
 	pub fn get_transformation(&self, m: Move) -> Self {
 		let mut to_return = self.clone();
-		to_return.transform(m);
+		to_return.force_make_move(m);
 		return to_return;
 	}
 
@@ -338,73 +737,81 @@ impl Board {
 			_ => panic!("There's no piece on {:?}!", source)
 		};
 
-		let side_bb = self.get_side_bitboard(side);
-		let side_bb = side_bb.transform(m);
-
-		let pieces_bb = self.get_pieces_bitboard(piece);
-		let pieces_bb = pieces_bb.transform(m);
+		let is_en_passant = (piece == Piece::Pawn) && (source.0 != m.1.0) && !self.is_occupied(m.1);
+		if is_en_passant {
+			self.capture_en_passant(side, m.1);
+		}
 
-		self.set_side_bitboard(side, side_bb);
-		self.set_pieces_bitboard(piece, pieces_bb);
-		self.disambiguate_captures(side, piece);
+		self.remove(m.1);
+		self.remove(source);
+		self.add(side, m.2.unwrap_or(piece), m.1);
 	}
 
-	fn disambiguate_captures(&mut self, side: Side, piece: Piece) {
-		let side_bb = self.get_side_bitboard(side);
+	fn capture_en_passant(&mut self, side: Side, destination: Square) {
 		let opponent = Side::get_opponent(side);
-		let opponent_bb = self.get_side_bitboard(opponent);
-
-		self.set_side_bitboard(opponent, opponent_bb & (side_bb.get_inverse()));
-
-		for p in Piece::all() {
-			self.priviledge_piece(piece, p);
-		}
+		match destination.get_adjacent(Direction::get_backward(side)) {
+			Some(captured) if self.get_side_pieces_bitboard(opponent, Piece::Pawn).contains(captured) => {
+				self.remove(captured);
+			},
+			_ => {}
+		};
 	}
 
-	fn priviledge_piece(&mut self, priviledged: Piece, to_overwrite: Piece) {
-		if priviledged == to_overwrite {
-			return;
+	fn get_check_suffix(&self, m: Move) -> String {
+		let (side, _piece) = match self.get(m.0) {
+			Some(t) => t,
+			None => panic!("No valid piece at the source of {:?}.  Board: {}", m, self.pretty_print())
+		};
+		let opponent = Side::get_opponent(side);
+		let transformation = self.get_transformation(m);
+		if !transformation.is_in_check(opponent) {
+			return "".to_string();
+		} else if transformation.has_no_legal_moves(opponent) {
+			return "#".to_string();
+		} else {
+			return "+".to_string();
 		}
-
-		let priviledged_bb = self.get_pieces_bitboard(priviledged);
-		let to_overwrite_bb = self.get_pieces_bitboard(to_overwrite);
-
-		self.set_pieces_bitboard(to_overwrite, to_overwrite_bb & (priviledged_bb.get_inverse()));
 	}
 
 	pub fn get_move_string(&self, m: Move) -> String {
+		format!("{}{}", self.get_move_string_without_check(m), self.get_check_suffix(m))
+	}
+
+	fn get_move_string_without_check(&self, m: Move) -> String {
 		let source = m.0;
 		let destination = m.1;
 		let (side, piece) = match self.get(source) {
 			Some(t) => t,
 			None => panic!("No valid piece at the source of {:?}.  Board: {}", m, self.pretty_print())
 		};
+		let capture = if self.is_capture(m) { "x" } else { "" };
 		if piece == Piece::Pawn {
-			if source.0 == destination.0 {
-				return destination.to_string();
+			if capture.is_empty() {
+				return format!("{}{}", destination.to_string(), m.get_promotion_string());
 			} else {
-				return format!("{}{}", source.0.to_string(), destination.to_string());
+				return format!("{}{}{}{}", source.0.to_string(), capture, destination.to_string(), m.get_promotion_string());
 			}
+		} else if self.get_castle_for_move(side, piece, m) == Some(Castle::Kingside) {
+			return "O-O".to_string();
+		} else if self.get_castle_for_move(side, piece, m) == Some(Castle::Queenside) {
+			return "O-O-O".to_string();
 		} else {
 			let potential_sources = self.get_potential_sources(side, piece, destination).to_squares();
-			if potential_sources.len() == 1 {
-				return format!("{}{}", piece.to_string(), destination.to_string());
-			} else if potential_sources.len() > 1 {
-				let first_source = potential_sources[0];
-				let file = first_source.0;
-				let rank = first_source.1;
-
-				if potential_sources.iter().filter(|x| x.0 == file).count() == 1 {
-					return format!("{}{}{}", piece.to_string(), file.to_string(), destination.to_string());
-				} else if potential_sources.iter().filter(|x| x.1 == rank).count() == 1 {
-					return format!("{}{}{}", piece.to_string(), rank.to_string(), destination.to_string());
-				} else {
-					panic!("This level of disambiguation is unsupported!");
-				}
-				
-			} else {
+			if !potential_sources.contains(&source) {
 				panic!("No sources for move: {:?}.  Board: {:?}", m, self.pretty_print());
 			}
+
+			let others: Vec<Square> = potential_sources.into_iter().filter(|x| *x != source).collect();
+			let disambiguation = if others.is_empty() {
+				"".to_string()
+			} else if others.iter().all(|x| x.0 != source.0) {
+				source.0.to_string()
+			} else if others.iter().all(|x| x.1 != source.1) {
+				source.1.to_string()
+			} else {
+				source.to_string()
+			};
+			return format!("{}{}{}{}", piece.to_string(), disambiguation, capture, destination.to_string());
 		}
 	}
 
@@ -413,12 +820,16 @@ impl Board {
 	}
 
 	pub fn try_parse_move(&self, side: Side, r#move: &str) -> Result<Move, String> {
-		
-		match r#move {
+		if Move::is_uci(r#move.trim()) {
+			return self.try_parse_uci_move(side, r#move.trim());
+		}
+
+		let normalized = Move::normalize_move_string(r#move);
+		match normalized.as_str() {
 			"O-O-O" => {
 				let castles = self.get_castles(side);
 				if castles.contains(&Castle::Queenside) {
-					return Ok(Castle::Queenside.get_king_move(side));
+					return Ok(self.get_castle_move(side, Castle::Queenside));
 				} else {
 					return Err(format!("Queenside castling is invalid in this position!  \nPieces: \n{:?}\nKings: \n{:?}\nRooks: \n{:?}", self.pieces().print(), self.kings.print(), self.rooks.print()));
 				}
@@ -426,7 +837,7 @@ impl Board {
 			"O-O" => {
 				let castles = self.get_castles(side);
 				if castles.contains(&Castle::Kingside) {
-					return Ok(Castle::Kingside.get_king_move(side));
+					return Ok(self.get_castle_move(side, Castle::Kingside));
 				} else {
 					return Err(format!("Kingside castling is invalid in this position!  \nPieces: \n{:?}\nKings: \n{:?}\nRooks: \n{:?}", self.pieces().print(), self.kings.print(), self.rooks.print()));
 				}
@@ -438,12 +849,13 @@ impl Board {
 						File::from_str(characters.get(1).map_or("", |m| m.as_str())),
 						Rank::from_str(characters.get(2).map_or("", |m| m.as_str()))
 					);
+					let promotion = characters.get(3).map(|m| Piece::from_str(m.as_str()));
 					
 					if self.is_occupied(destination) {
 						return Err(format!("Pawn cannot move forward onto an occupied space!  Move: {:?}", m));
 					}
 					let source_result = self.get_pawn_move_from_square_in_front(side, destination);
-					return source_result.map(|source| Move::new(source, destination));
+					return source_result.and_then(|source| Self::get_pawn_move(side, source, destination, promotion));
 				} else if DISAMBIGUATED_PAWN_MOVE.is_match(m) {
 					let characters = DISAMBIGUATED_PAWN_MOVE.captures(m).unwrap();
 					let destination = Square::new(
@@ -451,6 +863,7 @@ impl Board {
 						Rank::from_str(characters.get(3).map_or("", |m| m.as_str()))
 					);
 					let source_file = File::from_str(characters.get(1).map_or("", |m| m.as_str()));
+					let promotion = characters.get(4).map(|m| Piece::from_str(m.as_str()));
 					if File::distance(&source_file, &destination.file()) != 1 {
 						return Err(format!("{:?} and {:?} are not adjacent.", source_file, destination.file()));
 					}
@@ -461,11 +874,15 @@ impl Board {
 						Err(s) => return Err(s),
 						Ok(source) => {
 							match Rank::distance(source.1, destination.1) {
-								2 => {
-									// TODO check en passant rights
-									return Ok(Move::new(source, destination));
+								2 => return Err(format!("There is no pawn that can capture on {}!", destination.to_string())),
+								1 => {
+									let is_capture = self.get_side_bitboard(Side::get_opponent(side)).contains(destination);
+									let is_en_passant = (self.en_passant == Some(destination)) && self.is_en_passant_capturable(side, destination);
+									if !is_capture && !is_en_passant {
+										return Err(format!("There is nothing for the pawn on {} to capture on {}!", source.to_string(), destination.to_string()));
+									}
+									return Self::get_pawn_move(side, source, destination, promotion);
 								},
-								1 => return Ok(Move::new(source, destination)),
 								_ => panic!("get_source_pawn_from_square_in_front({:?}, {:?}) returned an invalid move: {:?}", side, destination, source)
 							};
 						}
@@ -495,24 +912,18 @@ impl Board {
 				} else if DISAMBIGUATED_PIECE_MOVE.is_match(m) {
 					let characters = DISAMBIGUATED_PIECE_MOVE.captures(m).unwrap();
 					let destination = Square::new(
-						File::from_str(characters.get(3).map_or("", |m| m.as_str())),
-						Rank::from_str(characters.get(4).map_or("", |m| m.as_str()))
+						File::from_str(characters.get(4).map_or("", |m| m.as_str())),
+						Rank::from_str(characters.get(5).map_or("", |m| m.as_str()))
 					);
 					let piece = Piece::from_str(characters.get(1).map_or("", |m| m.as_str()));
-					let source_file_or_rank = characters.get(2).map_or("", |m| m.as_str());
+					let source_file = characters.get(2).map(|m| File::from_str(m.as_str()));
+					let source_rank = characters.get(3).map(|m| Rank::from_str(m.as_str()));
 
 					if (self.get_side_bitboard(side) & Bitboard::square(destination)).has_pieces() {
 						return Err(format!("{:?} cannot move to an occupied square {:?}.", piece, destination));
 					}
 
-					let source_result;
-					if source_file_or_rank.chars().nth(0).unwrap().is_numeric() {
-						let source_rank = Rank::from_str(source_file_or_rank);
-						source_result = self.get_piece_source_on_rank(side, piece, destination, source_rank);
-					} else {
-						let source_file = File::from_str(source_file_or_rank);
-						source_result = self.get_piece_source_on_file(side, piece, destination, source_file);
-					}
+					let source_result = self.get_disambiguated_piece_source(side, piece, destination, source_file, source_rank);
 
 					match source_result {
 						Err(e) => return Err(e),
@@ -527,15 +938,40 @@ impl Board {
 		}
 	}
 
-	fn get_piece_source_on_file(&self, side: Side, piece: Piece, destination: Square, source_file: File) -> Result<Square, String> {
-		let potential_sources = (self.get_potential_sources(side, piece, destination) & Bitboard::file(source_file)).to_squares();
-		return self.get_unambiguous_source_from_potential_sources(side, piece, destination, potential_sources);
+	pub fn try_parse_uci_move(&self, side: Side, r#move: &str) -> Result<Move, String> {
+		let m = Move::from_uci(r#move)?;
+		let piece = match self.get(m.0) {
+			None => return Err(format!("{} is illegal: there's no piece on {}.", r#move, m.0.to_string())),
+			Some((s, _)) if s != side => return Err(format!("{} is illegal: the piece on {} isn't {}'s.", r#move, m.0.to_string(), side.to_string())),
+			Some((_, p)) => p,
+		};
+		if self.is_legal_move(m) {
+			return Ok(m);
+		}
+		let is_promotion = (piece == Piece::Pawn) && (m.1.rank() == Rank::promotion(side));
+		if is_promotion && m.2.is_none() {
+			return Err(format!("{} is illegal: a pawn moving to {} must promote, e.g. {}q.", r#move, m.1.to_string(), r#move));
+		}
+		return Err(format!("{} is illegal: the {} on {} can't move to {}.", r#move, piece.to_long_string(), m.0.to_string(), m.1.to_string()));
+	}
+
+	fn get_pawn_move(side: Side, source: Square, destination: Square, promotion: Option<Piece>) -> Result<Move, String> {
+		let is_promotion_rank = destination.rank() == Rank::promotion(side);
+		match (promotion, is_promotion_rank) {
+			(None, false) => Ok(Move::new(source, destination)),
+			(Some(p), true) => Ok(Move::new_promotion(source, destination, p)),
+			(None, true) => Err(format!("A pawn moving to {} must promote, e.g. {}=Q", destination.to_string(), destination.to_string())),
+			(Some(p), false) => Err(format!("A pawn can't promote to a {} on {}!", p.to_long_string(), destination.to_string())),
+		}
 	}
 
-	fn get_piece_source_on_rank(&self, side: Side, piece: Piece, destination: Square, source_rank: Rank) -> Result<Square, String> {
-		let potential_sources = (self.get_potential_sources(side, piece, destination) & Bitboard::rank(source_rank)).to_squares();
+	fn get_disambiguated_piece_source(&self, side: Side, piece: Piece, destination: Square, source_file: Option<File>, source_rank: Option<Rank>) -> Result<Square, String> {
+		let file_bb = source_file.map_or(Bitboard::empty().get_inverse(), Bitboard::file);
+		let rank_bb = source_rank.map_or(Bitboard::empty().get_inverse(), Bitboard::rank);
+		let potential_sources = (self.get_potential_sources(side, piece, destination) & file_bb & rank_bb).to_squares();
 		return self.get_unambiguous_source_from_potential_sources(side, piece, destination, potential_sources);
 	}
+
 	fn get_unambiguous_piece_source(&self, side: Side, piece: Piece, destination: Square) -> Result<Square, String> {
 		let potential_sources = self.get_potential_sources(side, piece, destination).to_squares();
 		return self.get_unambiguous_source_from_potential_sources(side, piece, destination, potential_sources);
@@ -555,7 +991,7 @@ impl Board {
 					self.get_side_pieces_bitboard(side, piece).print()
 				)
 			);
-		} else if potential_sources.len() > 2 {
+		} else if potential_sources.len() > 1 {
 			return Err(format!("Ambiguous potential sources for {:?} {:?}: {:?}.  Board:\n{}", side, piece, potential_sources, self.pieces().print()));
 		} else {
 			let source = potential_sources[0];
@@ -573,7 +1009,8 @@ impl Board {
 			Piece::Queen => (self.get_diagonal_vision(destination) | self.get_lateral_vision(destination)),
 			Piece::King => self.get_adjacent_vision(destination),
 		};
-		return vision & pieces;
+		let sources = (vision & pieces).to_squares();
+		return Bitboard::from_squares(sources.into_iter().filter(|s| !self.leaves_king_in_check(Move::new(*s, destination))).collect());
 	}
 
 	fn get_pawn_move_from_square_in_front(&self, side: Side, destination: Square) -> Result<Square, String> {
@@ -623,13 +1060,6 @@ impl Board {
 		}
 	}
 
-	fn set_side_bitboard(&mut self, side: Side, bitboard: Bitboard) {
-		match side {
-			Side::White => {self.white = bitboard},
-			Side::Black => {self.black = bitboard}
-		};
-	}
-
 	fn get_pieces_bitboard(&self, piece: Piece) -> Bitboard {
 		match piece {
 			Piece::Pawn => self.pawns,
@@ -689,160 +1119,394 @@ impl Board {
 			Piece::Queen => {
 				self.get_diagonal_moves(side, square) | self.get_lateral_moves(side, square)
 			}
-			Piece::King => {
-				let castles = self.get_castles(side);
-				self.get_adjacent_moves(side, square) | Bitboard::from_squares(castles.into_iter().map(|c| c.get_king_destination(side)).collect())
+			Piece::King => {
+				self.get_adjacent_moves(side, square)
+			}
+		};
+
+		let mut to_return = self.get_moves_not_into_check(square, to_return);
+		if piece == Piece::King {
+			let castles = self.get_castles(side).into_iter().map(|c| self.get_castle_move(side, c));
+			to_return.extend(castles.filter(|m| m.0 == square));
+		}
+		if piece == Piece::Pawn {
+			return to_return.into_iter().fold(
+				Vec::new(),
+				|mut accumulator, m| {
+					if m.1.rank() == Rank::promotion(side) {
+						accumulator.append(&mut m.with_promotions());
+					} else {
+						accumulator.push(m);
+					}
+					accumulator
+				}
+			);
+		}
+		return to_return;
+	}
+
+	pub fn perft(&self, side: Side, depth: usize) -> usize {
+		self.clone().perft_in_place(side, depth)
+	}
+
+	fn perft_in_place(&mut self, side: Side, depth: usize) -> usize {
+		match depth {
+			0 => 1,
+			1 => self.get_legal_moves_for_side(side).len(),
+			_ => self.divide_in_place(side, depth).into_iter().map(|(_, n)| n).sum()
+		}
+	}
+
+	pub fn divide(&self, side: Side, depth: usize) -> Vec<(Move, usize)> {
+		self.clone().divide_in_place(side, depth)
+	}
+
+	fn divide_in_place(&mut self, side: Side, depth: usize) -> Vec<(Move, usize)> {
+		let mut to_return = Vec::new();
+		if depth == 0 {
+			return to_return;
+		}
+		let opponent = Side::get_opponent(side);
+		for m in self.get_legal_moves_for_side(side) {
+			let undo = self.force_make_move(m);
+			to_return.push((m, self.perft_in_place(opponent, depth - 1)));
+			self.unmake_move(undo);
+		}
+		return to_return;
+	}
+
+	pub fn get_checks(&self, side: Side) -> Vec<Move> {
+		let mut board = self.clone();
+		let opponent = Side::get_opponent(side);
+		let mut to_return = Vec::new();
+		for m in self.get_legal_moves_for_side(side) {
+			let undo = board.force_make_move(m);
+			if board.is_in_check(opponent) {
+				to_return.push(m);
+			}
+			board.unmake_move(undo);
+		}
+		return to_return;
+	}
+
+	pub fn get_check_type(&self, m: Move) -> Option<CheckType> {
+		let (side, _) = self.get(m.0)?;
+		let n_checkers = self.get_transformation(m).get_checkers(side).count();
+		if n_checkers == 0 {
+			return None;
+		}
+		if n_checkers > 1 {
+			return Some(CheckType::Double);
+		}
+		return match self.get_unmasked_checkers(m).len() {
+			0 => Some(CheckType::Direct),
+			_ => Some(CheckType::Discovered)
+		};
+	}
+
+	pub fn get_unmasked_checkers(&self, m: Move) -> Vec<(Square, Piece)> {
+		let (side, _) = match self.get(m.0) {
+			Some(x) => x,
+			None => return Vec::new()
+		};
+		let before = self.get_checkers(side);
+		let board = self.get_transformation(m);
+		let after = board.get_checkers(side) & before.get_inverse();
+		return after.to_squares().into_iter()
+			.filter(|s| board.get(*s) == self.get(*s))
+			.map(|s| (s, board.get(s).unwrap().1))
+			.collect();
+	}
+
+	pub fn get_discovered_checks(&self, side: Side) -> Vec<Move> {
+		return self.get_checks(side).into_iter().filter(|m| !self.get_unmasked_checkers(*m).is_empty()).collect();
+	}
+
+	fn get_checkers(&self, side: Side) -> Bitboard {
+		let opponent = Side::get_opponent(side);
+		let kings = self.get_side_pieces_bitboard(opponent, Piece::King).to_squares();
+		return kings.into_iter().fold(Bitboard::empty(), |accumulator, k| accumulator | self.get_attackers_through(side, k, self.pieces()));
+	}
+
+	pub fn get_captures(&self, side: Side) -> Vec<Move> {
+		let legal_moves = self.get_legal_moves_for_side(side);
+		let opponent = Side::get_opponent(side);
+		return legal_moves.into_iter().filter(|m| self.is_capture(*m)).collect();
+	}
+
+	pub fn get_threats(&self, side: Side) -> Vec<Move> {
+		let opponent = Side::get_opponent(side);
+		let mut board = self.clone();
+		board.set_en_passant(None);
+		let checks = board.get_checks(opponent);
+		return board.get_legal_moves_for_side(opponent).into_iter()
+			.filter(|m| checks.contains(m) || (board.is_capture(*m) && (board.get_capture_exchange_evaluation(*m) > 0)))
+			.collect();
+	}
+
+	pub fn get_capture_exchange_evaluation(&self, m: Move) -> i32 {
+		let captured = match self.get_captured(m) {
+			Some((_, piece)) => piece.get_value() as i32,
+			None => return 0
+		};
+		let (side, _) = self.get(m.0).unwrap();
+		let recapture = self.get_transformation(m).get_static_exchange_evaluation(Side::get_opponent(side), m.1);
+		return captured - std::cmp::max(recapture, 0);
+	}
+
+	pub fn get_mating_moves(&self, side: Side) -> Vec<Move> {
+		let opponent = Side::get_opponent(side);
+		return self.get_legal_moves_for_side(side).into_iter().filter(|m| self.get_transformation(*m).is_checkmated(opponent)).collect();
+	}
+
+	pub fn get_mate_in(&self, side: Side, n: usize) -> Option<usize> {
+		self.search_mate_in(side, n, false)
+	}
+
+	pub fn get_mate_in_by_checks(&self, side: Side, n: usize) -> Option<usize> {
+		self.search_mate_in(side, n, true)
+	}
+
+	fn search_mate_in(&self, side: Side, n: usize, checks_only: bool) -> Option<usize> {
+		if n == 0 {
+			return None;
+		}
+		let mut board = self.clone();
+		let score = board.search_mate(side, 0, 2*n - 1, -MATE_SCORE, MATE_SCORE, checks_only);
+		match score > 0 {
+			true => Some(((MATE_SCORE - score) as usize).div_ceil(2)),
+			false => None
+		}
+	}
+
+	pub fn get_forced_mate_moves(&self, side: Side, n: usize) -> Vec<Move> {
+		if n == 0 {
+			return Vec::new();
+		}
+		let mut board = self.clone();
+		let opponent = Side::get_opponent(side);
+		let mut to_return = Vec::new();
+		for m in self.get_legal_moves_for_side(side) {
+			let undo = board.force_make_move(m);
+			if -board.search_mate(opponent, 1, 2*n - 1, -MATE_SCORE, MATE_SCORE, false) > 0 {
+				to_return.push(m);
+			}
+			board.unmake_move(undo);
+		}
+		return to_return;
+	}
+
+	pub fn validate_forced_mate_line(&self, side: Side, n: usize, moves: Vec<Move>) -> Result<(), String> {
+		if n == 0 {
+			return Err("A forced mate needs at least one move.".to_string());
+		}
+		if moves.len().is_multiple_of(2) || (moves.len() > 2*n - 1) {
+			return Err(format!("A mate in {} is an odd number of moves, at most {}, but {} were given.", n, 2*n - 1, moves.len()));
+		}
+
+		let mut board = self.clone();
+		let mut next_to_act = side;
+		for (i, m) in moves.into_iter().enumerate() {
+			if !board.is_legal_move(m) {
+				return Err(format!("{} is not a legal move.", m.to_uci()));
+			}
+			let remaining = n - i/2;
+			if (next_to_act == side) && !board.get_forced_mate_moves(side, remaining).contains(&m) {
+				return Err(format!("{} doesn't force mate in {}.", board.get_move_string(m), remaining));
 			}
-		};
+			board.make_move(m);
+			next_to_act = Side::get_opponent(next_to_act);
+		}
 
-		let to_return = self.get_moves_not_into_check(square, to_return);
-		return to_return;
+		if !board.is_checkmated(next_to_act) {
+			return Err("The line doesn't end in checkmate.".to_string());
+		}
+		return Ok(());
 	}
 
-	pub fn get_checks(&self, side: Side) -> Vec<Move> {
+	fn search_mate(&mut self, side: Side, ply: usize, max_ply: usize, alpha: i32, beta: i32, checks_only: bool) -> i32 {
 		let legal_moves = self.get_legal_moves_for_side(side);
+		if legal_moves.is_empty() {
+			return if self.is_in_check(side) { -(MATE_SCORE - ply as i32) } else { 0 };
+		}
+		if ply >= max_ply {
+			return 0;
+		}
+
+		// Only a check can mate, so the attacker's last move needn't consider anything else.
+		let is_checks_only = (ply + 1 == max_ply) || (checks_only && ply.is_multiple_of(2));
+		let opponent = Side::get_opponent(side);
+		let mut alpha = alpha;
+		let mut best = if is_checks_only { 0 } else { -MATE_SCORE };
+		for (m, is_check) in self.order_forcing_moves(side, legal_moves) {
+			if is_checks_only && !is_check {
+				break;
+			}
+			let undo = self.force_make_move(m);
+			let score = -self.search_mate(opponent, ply + 1, max_ply, -beta, -alpha, checks_only);
+			self.unmake_move(undo);
+			best = std::cmp::max(best, score);
+			alpha = std::cmp::max(alpha, best);
+			if alpha >= beta {
+				break;
+			}
+		}
+		return best;
+	}
+
+	fn order_forcing_moves(&mut self, side: Side, moves: Vec<Move>) -> Vec<(Move, bool)> {
 		let opponent = Side::get_opponent(side);
-		return legal_moves.into_iter().filter(|m| self.get_transformation(*m).is_in_check(opponent)).collect();
+		let mut to_return: Vec<(Move, bool, bool)> = moves.into_iter().map(|m| {
+			let is_capture = self.is_capture(m);
+			let undo = self.force_make_move(m);
+			let is_check = self.is_in_check(opponent);
+			self.unmake_move(undo);
+			(m, is_check, is_capture)
+		}).collect();
+		to_return.sort_by_key(|(_, is_check, is_capture)| (!is_check, !is_capture));
+		return to_return.into_iter().map(|(m, is_check, _)| (m, is_check)).collect();
+	}
+
+	pub fn get_forking_moves(&self, side: Side) -> Vec<Move> {
+		let mut board = self.clone();
+		let opponent = Side::get_opponent(side);
+		let mut to_return = Vec::new();
+		for m in self.get_legal_moves_for_side(side) {
+			let undo = board.force_make_move(m);
+			let n_targets = board.get_side_squares(opponent).into_iter().filter(|target| {
+				let (_, piece) = board.get(*target).unwrap();
+				(piece != Piece::Pawn) && board.has_vision(m.1, *target)
+			}).count();
+			if n_targets >= 2 {
+				to_return.push(m);
+			}
+			board.unmake_move(undo);
+		}
+		return to_return;
 	}
 
-	pub fn get_captures(&self, side: Side) -> Vec<Move> {
-		let legal_moves = self.get_legal_moves_for_side(side);
+	pub fn get_pins(&self, side: Side) -> Vec<Pin> {
 		let opponent = Side::get_opponent(side);
-		return legal_moves.into_iter().filter(|m| self.is_capture(*m)).collect();
+		let mut to_return = Vec::new();
+		for pinner in self.get_side_squares(opponent) {
+			let pinner_piece = self.get(pinner).unwrap().1;
+			let directions = match pinner_piece {
+				Piece::Bishop => Direction::diagonals(),
+				Piece::Rook => Direction::laterals(),
+				Piece::Queen => Direction::all(),
+				_ => Vec::new()
+			};
+			for direction in directions {
+				let mut pieces = self.get_ray_pieces(pinner, direction).into_iter();
+				if let (Some((pinned, (s1, p1))), Some((target, (s2, p2)))) = (pieces.next(), pieces.next()) {
+					let is_pin = (s1 == side) && (s2 == side) && (p1 != Piece::King) && ((p2 == Piece::King) || (p2.get_value() > p1.get_value()));
+					if is_pin {
+						to_return.push(Pin::new((pinner, pinner_piece), (pinned, p1), (target, p2)));
+					}
+				}
+			}
+		}
+		return to_return;
+	}
+
+	fn get_ray_pieces(&self, square: Square, direction: Direction) -> Vec<(Square, (Side, Piece))> {
+		let mut to_return = Vec::new();
+		let mut next = square.get_adjacent(direction);
+		while let Some(s) = next {
+			if let Some(p) = self.get(s) {
+				to_return.push((s, p));
+			}
+			next = s.get_adjacent(direction);
+		}
+		return to_return;
 	}
 
 	pub fn is_capture(&self, m: Move) -> bool {
 		let source = m.0;
-		let (side, _piece) = match self.get(m.0) {
+		let (side, piece) = match self.get(m.0) {
 			Some(t) => t,
 			None => panic!("There's nothing to move there: {:?}\nBoard: {}", m, self.pretty_print())
 		};
 		let opponent = Side::get_opponent(side);
 		let destination = m.1;
 		match self.get(destination) {
-			None => false,
+			None => (piece == Piece::Pawn) && (source.0 != destination.0),
 			Some((s, _)) => opponent == s
 		}
 	}
 
 	fn get_moves_not_into_check(&self, source: Square, bitboard: Bitboard) -> Vec<Move> {
-		let destinations = bitboard.to_squares();
-		let (side, piece) = match self.get(source) {
-			Some(p) => p,
-			_ => panic!("There's no piece on {:?}!", source)
-		};
-		return destinations.into_iter()
-			.filter(|destination| !self.get_transformation(Move::new(source, *destination)).is_in_check(side))
+		return bitboard.to_squares().into_iter()
 			.map(|destination| Move::new(source, destination))
+			.filter(|m| !self.leaves_king_in_check(*m))
 			.collect();
 
 	}
 
-	fn get_en_passant_takes(&self, side: Side, square: Square) -> Bitboard {
-		let prior_move = match side {
-			Side::White => self.prior_move_white,
-			Side::Black => self.prior_move_black
-		};
-		Bitboard::empty() // TODO
-	}
-
-	fn get_diagonal_pawn_takes(&self, side: Side, square: Square) -> Bitboard {
-		let directions = Direction::get_forward_diagonals(side);
-
-		let potential_captures = match (square.get_adjacent(directions.0), square.get_adjacent(directions.1)) {
-			(None, None) => Bitboard::empty(),
-			(None, Some(r)) => Bitboard::square(r),
-			(Some(l), None) => Bitboard::square(l),
-			(Some(l), Some(r)) => Bitboard::from_squares(vec![l, r])
-		};
-		let opponent_pieces = self.get_side_bitboard(Side::get_opponent(side));
-		return potential_captures & opponent_pieces;
+	pub fn get_en_passant(&self) -> Option<Square> {
+		self.en_passant
 	}
 
-	fn get_forward_pawn_moves(&self, side: Side, square: Square) -> Bitboard {
-		let direction = Direction::get_forward(side);
-		let mut to_return = match square.get_adjacent(direction) {
+	fn get_en_passant_takes(&self, side: Side, square: Square) -> Bitboard {
+		match self.en_passant {
 			None => Bitboard::empty(),
-			Some(s) => {
-				match self.is_occupied(s) {
-					true => Bitboard::empty(),
-					false => Bitboard::square(s)
+			Some(target) => {
+				if self.is_en_passant_capturable(side, target) {
+					self.get_immediately_diagonal_and_forward_vision(side, square) & Bitboard::square(target)
+				} else {
+					Bitboard::empty()
 				}
 			}
-		};
-		let is_first_pawn_move = square.1 == Rank::first_pawn_move(side);
-		if is_first_pawn_move && (to_return.has_pieces()) {
-			match square.get_adjacent(direction).map(|x| x.get_adjacent(direction)).flatten() {
-				None => { panic!("Pawn moving two steps would run off the board!  {:?}", square); }
-				Some(s) => {
-					match self.is_occupied(s) {
-						true => {},
-						false => {
-							to_return = to_return | Bitboard::square(s);
-						}
-					}
-				}
-			};
 		}
+	}
+
+	fn is_en_passant_capturable(&self, side: Side, target: Square) -> bool {
+		let opponent = Side::get_opponent(side);
+		match target.get_adjacent(Direction::get_backward(side)) {
+			None => false,
+			Some(captured) => self.get_side_pieces_bitboard(opponent, Piece::Pawn).contains(captured)
+		}
+	}
 
-		return to_return;
+	fn get_diagonal_pawn_takes(&self, side: Side, square: Square) -> Bitboard {
+		Bitboard::pawn_attacks(side, square) & self.get_opponent(side)
+	}
 
+	fn get_forward_pawn_moves(&self, side: Side, square: Square) -> Bitboard {
+		let empty = self.pieces().get_inverse();
+		let one_step = Bitboard::square(square).shift_forward(side) & empty;
+		if square.1 == Rank::first_pawn_move(side) {
+			return one_step | (one_step.shift_forward(side) & empty);
+		}
+		return one_step;
 	}
 
 	fn get_castles(&self, side: Side) -> Vec<Castle> {
-		let rank = Castle::get_rank(side);
-		let is_king_on_e = (self.get_side_pieces_bitboard(side, Piece::King) & Bitboard::file(File::E)).has_pieces();
-		if !is_king_on_e {
+		let king_square = self.get_castle_king_square(side);
+		if !self.get_side_pieces_bitboard(side, Piece::King).contains(king_square) {
 			return Vec::new();
 		}
 
-		let rooks = self.get_side_pieces_bitboard(side, Piece::Rook);
-		let is_kingside_rook_in_place = (rooks & Bitboard::square(Square::new(File::H, rank))).has_pieces();
-		let is_queenside_rook_in_place = (rooks & Bitboard::square(Square::new(File::A, rank))).has_pieces();
-
-		let kingside_blockers = vec![
-			Square::new(File::F, rank),
-			Square::new(File::G, rank),
-		];
-		let is_kingside_blocked = (Bitboard::from_squares(kingside_blockers) & self.pieces()).has_pieces();
-
-		let queenside_blockers = vec![
-			Square::new(File::B, rank),
-			Square::new(File::C, rank),
-			Square::new(File::D, rank),
-		];
-		let is_queenside_blocked = (Bitboard::from_squares(queenside_blockers) & self.pieces()).has_pieces();
-
 		let opponent = Side::get_opponent(side);
-		let kingside_king_squares = vec![
-			Square::new(File::E, rank),
-			Square::new(File::F, rank),
-			Square::new(File::G, rank),
-		];
-		let is_kingside_attacked = kingside_king_squares.iter().any(|x| self.is_attacking(opponent, *x));
-
-		let queenside_king_squares = vec![
-			Square::new(File::C, rank),
-			Square::new(File::D, rank),
-			Square::new(File::E, rank),
-		];
-		let is_queenside_attacked = queenside_king_squares.iter().any(|x| self.is_attacking(opponent, *x));
-
-		let (castling_rights_kingside, castling_rights_queenside) = match side {
-			Side::White => (self.castling_rights_white_kingside, self.castling_rights_white_queenside),
-			Side::Black => (self.castling_rights_black_kingside, self.castling_rights_black_queenside),
-		};
-
-		let can_castle_kingside = is_kingside_rook_in_place && !is_kingside_blocked && !is_kingside_attacked && castling_rights_kingside;
-		let can_castle_queenside = is_queenside_rook_in_place && !is_queenside_blocked && !is_queenside_attacked && castling_rights_queenside;
+		let rooks = self.get_side_pieces_bitboard(side, Piece::Rook);
+		return Castle::all().into_iter().filter(|castle| {
+			let rook_square = self.get_castle_rook_square(side, *castle);
+			if !self.get_castling_rights(side, *castle) || !rooks.contains(rook_square) {
+				return false;
+			}
 
-		match (can_castle_kingside, can_castle_queenside) {
-			(false, false) => Vec::new(),
-			(true, false) => vec![Castle::Kingside],
-			(false, true) => vec![Castle::Queenside],
-			(true, true) => vec![Castle::Kingside, Castle::Queenside]
-		}
+			let king_destination = castle.get_king_destination(side);
+			let rook_destination = castle.get_rook_destination(side);
+			let occupancy = self.pieces() & (Bitboard::square(king_square) | Bitboard::square(rook_square)).get_inverse();
+			let king_path = Bitboard::between_on_rank(king_square, king_destination);
+			let rook_path = Bitboard::between_on_rank(rook_square, rook_destination);
+			if ((king_path | rook_path) & occupancy).has_pieces() {
+				return false;
+			}
 
+			let attackers = self.get_side(opponent);
+			return !king_path.to_squares().into_iter().any(|s| self.is_attacking_through(opponent, s, occupancy, attackers));
+		}).collect();
 	}
 
 	fn get_adjacent_moves(&self, side: Side, square: Square) -> Bitboard {
@@ -862,66 +1526,33 @@ impl Board {
 	}
 
 	fn get_diagonal_vision(&self, square: Square) -> Bitboard {
-		let up_left = self.get_vision_directional(square, Direction::UpLeft);
-		let up_right = self.get_vision_directional(square, Direction::UpRight);
-		let down_left = self.get_vision_directional(square, Direction::DownLeft);
-		let down_right = self.get_vision_directional(square, Direction::DownRight);
-		up_left | up_right | down_left | down_right
+		Bitboard::diagonal_attacks(square, self.pieces())
 	}
 
 	fn get_immediately_diagonal_vision(&self, square: Square) -> Bitboard {
-		let squares = self.get_adjacents_from_directions(square, Direction::diagonals());
-		Bitboard::from_squares(squares)
+		Bitboard::pawn_attacks(Side::White, square) | Bitboard::pawn_attacks(Side::Black, square)
 	}
 
 	fn get_immediately_diagonal_and_forward_vision(&self, side: Side, square: Square) -> Bitboard {
-		let forward_diagonals = Direction::get_forward_diagonals(side);
-		let vectorized_directions = vec![forward_diagonals.0, forward_diagonals.1];
-		let squares = self.get_adjacents_from_directions(square, vectorized_directions);
-		Bitboard::from_squares(squares)
-	}
-
-	fn get_immediately_diagonal_and_backward_vision(&self, side: Side, square: Square) -> Bitboard {
-		let backward_diagonals = Direction::get_forward_diagonals(Side::get_opponent(side));
-		let vectorized_directions = vec![backward_diagonals.0, backward_diagonals.1];
-		let squares = self.get_adjacents_from_directions(square, vectorized_directions);
-		Bitboard::from_squares(squares)
-	}
-
-	fn get_adjacents_from_directions(&self, square: Square, directions: Vec<Direction>) -> Vec<Square> {
-		directions.iter().map(|d| square.get_adjacent(*d)).filter(|x| !x.is_none()).map(|x| x.unwrap()).collect()
+		Bitboard::pawn_attacks(side, square)
 	}
 
 	fn get_adjacent_vision(&self, square: Square) -> Bitboard {
-		let squares = Direction::all().iter().map(|d| square.get_adjacent(*d)).filter(|x| !x.is_none()).map(|x| x.unwrap()).collect();
-		Bitboard::from_squares(squares)
+		Bitboard::king_attacks(square)
 	}
 
 	fn get_knight_vision(&self, square: Square) -> Bitboard {
-		let squares = Direction::knight_moves().iter().map(|moves| square.get_relative(moves.to_vec())).filter(|x| !x.is_none()).map(|x| x.unwrap()).collect();
-		Bitboard::from_squares(squares)
+		Bitboard::knight_attacks(square)
 	}
 
 	fn get_lateral_vision(&self, square: Square) -> Bitboard {
-		self.get_file_vision(square) | self.get_rank_vision(square)
+		Bitboard::lateral_attacks(square, self.pieces())
 	}
 
 	fn get_queen_vision(&self, square: Square) -> Bitboard {
 		self.get_diagonal_vision(square) | self.get_lateral_vision(square)
 	}
 
-	fn get_file_vision(&self, square: Square) -> Bitboard {
-		let right = self.get_vision_directional(square, Direction::Right);
-		let left = self.get_vision_directional(square, Direction::Left);
-		right | left
-	}
-
-	fn get_rank_vision(&self, square: Square) -> Bitboard {
-		let up = self.get_vision_directional(square, Direction::Up);
-		let down = self.get_vision_directional(square, Direction::Down);
-		up | down
-	}
-
 	fn get_pieces_on_file(&self, file: File) -> Bitboard {
 		self.pieces() & Bitboard::file(file)
 	}
@@ -930,37 +1561,6 @@ impl Board {
 		self.pieces() & Bitboard::rank(rank)
 	}
 
-	fn get_vision_directional(&self, square: Square, direction: Direction) -> Bitboard {
-		let mut file = Some(square.0);
-		let mut rank = Some(square.1);
-		let mut to_return = Bitboard::empty();
-		while (!file.is_none()) && (!rank.is_none()) {
-			let (f, r) = match direction {
-				Direction::Up => (file, rank.unwrap().next()),
-				Direction::Down => (file, rank.unwrap().previous()),
-				Direction::Left => (file.unwrap().previous(), rank),
-				Direction::Right => (file.unwrap().next(), rank),
-				Direction::UpLeft => (file.unwrap().previous(), rank.unwrap().next()),
-				Direction::UpRight => (file.unwrap().next(), rank.unwrap().next()),
-				Direction::DownLeft => (file.unwrap().previous(), rank.unwrap().previous()),
-				Direction::DownRight => (file.unwrap().next(), rank.unwrap().previous()),
-			};
-			file = f;
-			rank = r;
-			if f.is_none() || r.is_none() {
-				break;
-			}
-			let test_square = Square(file.unwrap(), rank.unwrap());
-			to_return = to_return | Bitboard::square(test_square);
-			if self.is_occupied(test_square) {
-				break;
-			}
-		}
-
-		return to_return;
-	}
-
-
 	fn get_side(&self, side: Side) -> Bitboard {
 		match side {
 			Side::White => self.white,
@@ -1023,6 +1623,137 @@ impl Fen {
 		return strings.join(" ");
 	}
 
+	fn try_parse(fen: &str) -> Result<(Board, Side, usize, usize), String> {
+		let fields: Vec<&str> = fen.split_whitespace().collect();
+		if fields.len() < 4 || fields.len() > 6 {
+			return Err(format!("A FEN needs 4 to 6 space-separated fields but {:?} has {}.", fen, fields.len()));
+		}
+
+		let mut board = Self::parse_pieces(fields[0])?;
+		let side = Self::parse_side(fields[1])?;
+		Self::parse_castling(&mut board, fields[2])?;
+		board.set_en_passant(Self::parse_en_passant(side, fields[3])?);
+		let half_moves = match fields.get(4) {
+			None => 0,
+			Some(h) => Self::parse_counter(h, "halfmove clock")?,
+		};
+		let full_moves = match fields.get(5) {
+			None => 1,
+			Some(f) => Self::parse_counter(f, "fullmove number")?,
+		};
+		if full_moves == 0 {
+			return Err("The fullmove number starts at 1, not 0.".to_string());
+		}
+
+		for s in Side::all() {
+			let n_kings = board.get_side_pieces(s, Piece::King).len();
+			if n_kings != 1 {
+				return Err(format!("{} must have exactly one king but has {}.", s.to_string(), n_kings));
+			}
+		}
+		if board.is_in_check(Side::get_opponent(side)) {
+			return Err(format!("{} is in check but it's {}'s move.", Side::get_opponent(side).to_string(), side.to_string()));
+		}
+
+		return Ok((board, side, half_moves, full_moves));
+	}
+
+	fn parse_pieces(pieces: &str) -> Result<Board, String> {
+		let ranks: Vec<&str> = pieces.split("/").collect();
+		if ranks.len() != 8 {
+			return Err(format!("The piece placement {:?} should have 8 ranks but has {}.", pieces, ranks.len()));
+		}
+
+		let mut board = Board::empty();
+		for (rank, rank_string) in Rank::all().into_iter().rev().zip(ranks) {
+			let mut file_index: u64 = 0;
+			for c in rank_string.chars() {
+				if let Some(n) = c.to_digit(10) {
+					if n == 0 || n > 8 {
+						return Err(format!("{:?} is not a valid number of empty squares on rank {}.", c, rank.to_string()));
+					}
+					file_index += n as u64;
+				} else if Piece::is_piece_char(c.to_ascii_uppercase()) {
+					if file_index >= 8 {
+						return Err(format!("Rank {} has more than 8 squares: {:?}", rank.to_string(), rank_string));
+					}
+					let side = if c.is_uppercase() { Side::White } else { Side::Black };
+					let piece = Piece::from_char(c.to_ascii_uppercase());
+					if (piece == Piece::Pawn) && ((rank == Rank::One) || (rank == Rank::Eight)) {
+						return Err(format!("Pawns can't be on rank {}.", rank.to_string()));
+					}
+					board.add(side, piece, Square::new(File::from_u64(file_index), rank));
+					file_index += 1;
+				} else {
+					return Err(format!("{:?} is not a valid piece character.", c));
+				}
+			}
+			if file_index != 8 {
+				return Err(format!("Rank {} should have 8 squares but has {}: {:?}", rank.to_string(), file_index, rank_string));
+			}
+		}
+		return Ok(board);
+	}
+
+	fn parse_side(side: &str) -> Result<Side, String> {
+		match side {
+			"w" => Ok(Side::White),
+			"b" => Ok(Side::Black),
+			s => Err(format!("The side to move must be \"w\" or \"b\" but is {:?}.", s)),
+		}
+	}
+
+	fn parse_castling(board: &mut Board, castling: &str) -> Result<(), String> {
+		for side in Side::all() {
+			for castle in Castle::all() {
+				board.set_castling_rights(side, castle, false);
+			}
+		}
+		if castling == "-" {
+			return Ok(());
+		}
+		for c in castling.chars() {
+			let side = if c.is_uppercase() { Side::White } else { Side::Black };
+			let king_square = board.get_back_rank_king_square(side);
+			let (castle, rook_file) = match c.to_ascii_lowercase() {
+				'k' => (Castle::Kingside, Self::get_outermost_rook_file(board, side, Castle::Kingside)),
+				'q' => (Castle::Queenside, Self::get_outermost_rook_file(board, side, Castle::Queenside)),
+				'a'..='h' => {
+					let rook_file = File::from_char(c.to_ascii_lowercase());
+					let castle = match king_square {
+						Some(k) if k.0 != rook_file => Castle::from_files(k.0, rook_file),
+						_ => return Err(format!("{:?} in {:?} needs {}'s king on its back rank and away from the {}-file.", c, castling, side.to_string(), rook_file.to_string())),
+					};
+					(castle, rook_file)
+				},
+				_ => return Err(format!("{:?} is not a valid castling character in {:?}.", c, castling)),
+			};
+			board.castling_rook_files[Castle::get_index(side, castle)] = rook_file;
+			board.set_castling_rights(side, castle, true);
+		}
+		board.chess960 = board.has_chess960_castling();
+		return Ok(());
+	}
+
+	fn parse_en_passant(side: Side, en_passant: &str) -> Result<Option<Square>, String> {
+		if en_passant == "-" {
+			return Ok(None);
+		}
+		let square = Square::try_parse(en_passant)?;
+		let expected_rank = match side {
+			Side::White => Rank::Six,
+			Side::Black => Rank::Three,
+		};
+		if square.rank() != expected_rank {
+			return Err(format!("The en passant square must be on rank {} when {} is to move but is {}.", expected_rank.to_string(), side.to_string(), square.to_string()));
+		}
+		return Ok(Some(square));
+	}
+
+	fn parse_counter(counter: &str, name: &str) -> Result<usize, String> {
+		counter.parse::<usize>().map_err(|_| format!("The {} must be a non-negative number but is {:?}.", name, counter))
+	}
+
 	fn get_pieces(board: &Board) -> String {
 		let mut to_return = Vec::new();
 		for rank in Rank::all().into_iter().rev() {
@@ -1057,23 +1788,36 @@ impl Fen {
 	}
 
 	fn get_en_passant(board: &Board) -> Option<Square> {
-		// TODO: Implement en passant!
-		return None;
+		board.get_en_passant()
 	}
 
 	fn get_castling(board: &Board) -> String {
+		Self::get_castling_characters(board, false)
+	}
+
+	fn get_shredder_castling(board: &Board) -> String {
+		Self::get_castling_characters(board, true)
+	}
+
+	fn get_castling_characters(board: &Board, is_shredder: bool) -> String {
 		let mut to_return = Vec::new();
-		if board.castling_rights_white_kingside {
-			to_return.push("K");
-		}
-		if board.castling_rights_white_queenside {
-			to_return.push("Q");
-		}
-		if board.castling_rights_black_kingside {
-			to_return.push("k");
-		}
-		if board.castling_rights_black_queenside {
-			to_return.push("q");
+		for side in Side::all() {
+			for castle in Castle::all() {
+				if !board.get_castling_rights(side, castle) {
+					continue;
+				}
+				let rook_file = board.get_castle_rook_square(side, castle).0;
+				let is_outermost = Self::get_outermost_rook_file(board, side, castle) == rook_file;
+				let c = if is_shredder || (board.is_chess960() && !is_outermost) {
+					rook_file.to_string()
+				} else {
+					castle.to_string()
+				};
+				to_return.push(match side {
+					Side::White => c.to_uppercase(),
+					Side::Black => c,
+				});
+			}
 		}
 
 		if to_return.len() == 0 {
@@ -1083,6 +1827,21 @@ impl Fen {
 		}
 	}
 
+	fn get_outermost_rook_file(board: &Board, side: Side, castle: Castle) -> File {
+		let standard = castle.get_rook_source(side).0;
+		let king_file = match board.get_back_rank_king_square(side) {
+			Some(k) => k.0,
+			None => return standard
+		};
+		let rooks = board.get_side_pieces_bitboard(side, Piece::Rook) & Bitboard::rank(Castle::get_rank(side));
+		let files = rooks.to_squares().into_iter().map(|s| s.0).filter(|f| Castle::from_files(king_file, *f) == castle && *f != king_file);
+		let outermost = match castle {
+			Castle::Kingside => files.max_by_key(|f| *f as u64),
+			Castle::Queenside => files.min_by_key(|f| *f as u64),
+		};
+		return outermost.unwrap_or(standard);
+	}
+
 	fn get_castle_type(castles: Vec<Castle>) -> String {
 		let mut to_return = Vec::new();
 		if castles.contains(&Castle::Kingside) {
@@ -1103,6 +1862,31 @@ pub enum Castle {
 
 impl Castle {
 
+	pub fn all() -> Vec<Self> {
+		vec![Castle::Kingside, Castle::Queenside]
+	}
+
+	fn get_index(side: Side, castle: Castle) -> usize {
+		2*(side as usize) + (castle as usize)
+	}
+
+	fn from_files(king_file: File, rook_file: File) -> Self {
+		match (rook_file as u64) > (king_file as u64) {
+			true => Castle::Kingside,
+			false => Castle::Queenside
+		}
+	}
+
+	fn get_standard_rook_files() -> [File; 4] {
+		let mut to_return = [File::H; 4];
+		for side in Side::all() {
+			for castle in Castle::all() {
+				to_return[Self::get_index(side, castle)] = castle.get_rook_source(side).0;
+			}
+		}
+		return to_return;
+	}
+
 	pub fn get_king_move(&self, side: Side) -> Move {
 		let source = Square::new(File::E, Self::get_rank(side));
 		let destination = self.get_king_destination(side);
@@ -1150,6 +1934,16 @@ impl Castle {
 	}
 }
 
+impl fmt::Display for Castle {
+
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Castle::Kingside => write!(f, "k"),
+			Castle::Queenside => write!(f, "q"),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Side {
 	White,
@@ -1290,7 +2084,7 @@ impl Path {
 	}
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Piece {
 	Pawn,
 	Knight,
@@ -1358,6 +2152,17 @@ impl Piece {
 		}
 	}
 
+	pub fn get_value(&self) -> usize {
+		match self {
+			Piece::Pawn => 1,
+			Piece::Knight => 3,
+			Piece::Bishop => 3,
+			Piece::Rook => 5,
+			Piece::Queen => 9,
+			Piece::King => 100,
+		}
+	}
+
 	pub fn to_long_string(&self) -> String {
 		match self {
 			Piece::Pawn => "Pawn".to_string(),
@@ -1390,6 +2195,15 @@ impl Piece {
 		]
 	}
 
+	pub fn promotions() -> Vec<Self> {
+		vec![
+			Piece::Queen,
+			Piece::Rook,
+			Piece::Bishop,
+			Piece::Knight
+		]
+	}
+
 	pub fn get_shortest_paths(&self, start: Square, end: Square) -> HashSet<Path> {
 		let board = Board::singleton(Side::White, *self, start);
 		let mut calculator = ShortestPathCalculator::new(board, start, end);
@@ -1405,6 +2219,7 @@ pub struct ShortestPathCalculator {
 	candidates: VecDeque<Path>,
 	results: HashSet<Path>,
 	shortest_so_far: usize,
+	distances: HashMap<Square, usize>,
 }
 
 impl ShortestPathCalculator {
@@ -1423,6 +2238,7 @@ impl ShortestPathCalculator {
 			candidates,
 			results: HashSet::new(),
 			shortest_so_far: 99,
+			distances: vec![(starting_position, 0)].into_iter().collect(),
 		}
 	}
 
@@ -1459,31 +2275,188 @@ impl ShortestPathCalculator {
 		}
 	}
 
-	fn check_layer(&mut self, head: Path, board: Board, square: Square) {
-		let new_candidates = board.get_legal_moves(square);
-		for candidate in new_candidates {
-			let mut path = head.clone();
-			path.push(candidate);
-			if candidate.1 == self.ending_position {
-				self.results.insert(path.clone());
-				self.update_shortest_so_far(path.len())
-			}
-			self.candidates.push_back(path)
+	fn check_layer(&mut self, head: Path, board: Board, square: Square) {
+		let new_candidates = board.get_legal_moves(square);
+		for candidate in new_candidates {
+			let mut path = head.clone();
+			path.push(candidate);
+			// Every prefix of a shortest path is itself a shortest path, so longer routes to a square can be dropped.
+			let distance = *self.distances.entry(candidate.1).or_insert(path.len());
+			if path.len() > distance {
+				continue;
+			}
+			if candidate.1 == self.ending_position {
+				self.results.insert(path.clone());
+				self.update_shortest_so_far(path.len())
+			}
+			self.candidates.push_back(path)
+		}
+	}
+
+	fn update_shortest_so_far(&mut self, l: usize) {
+		self.shortest_so_far = if l < self.shortest_so_far { l } else { self.shortest_so_far };
+	}
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.zobrist.hash(state);
+	}
+}
+
+const ZOBRIST_SEED: u64 = 0x5EED_C0DE_CAFE_F00D;
+
+#[derive(Debug)]
+struct Zobrist {
+	pieces: Vec<u64>,
+	castling: Vec<u64>,
+	en_passant: Vec<u64>,
+	side: u64,
+}
+
+impl Zobrist {
+
+	fn new() -> Self {
+		// A fixed splitmix64 stream keeps keys stable across runs and crate versions.
+		let mut state = ZOBRIST_SEED;
+		let mut next = || {
+			state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+			let mut z = state;
+			z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+			z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+			z ^ (z >> 31)
+		};
+		Self {
+			pieces: (0..(2*6*64)).map(|_| next()).collect(),
+			castling: (0..4).map(|_| next()).collect(),
+			en_passant: (0..8).map(|_| next()).collect(),
+			side: next(),
+		}
+	}
+
+	fn piece(&self, side: Side, piece: Piece, square: Square) -> u64 {
+		self.pieces[64*(6*(side as usize) + (piece as usize)) + square.to_index()]
+	}
+
+	fn castling(&self, side: Side, castle: Castle) -> u64 {
+		self.castling[Castle::get_index(side, castle)]
+	}
+
+	fn en_passant(&self, target: Square) -> u64 {
+		self.en_passant[target.file() as usize]
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Move(Square, Square, Option<Piece>);
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Undo {
+	m: Move,
+	side: Side,
+	piece: Piece,
+	captured: Option<(Square, Piece)>,
+	castle: Option<Castle>,
+	en_passant: Option<Square>,
+	castling_rights: [bool; 4],
+}
+
+impl Undo {
+
+	pub fn get_move(&self) -> Move {
+		self.m
+	}
+
+	pub fn get_captured(&self) -> Option<(Square, Piece)> {
+		self.captured
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum CheckType {
+	Direct,
+	Discovered,
+	Double,
+}
+
+impl fmt::Display for CheckType {
+
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			CheckType::Direct => write!(f, "direct"),
+			CheckType::Discovered => write!(f, "discovered"),
+			CheckType::Double => write!(f, "double"),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Pin {
+	pinner: (Square, Piece),
+	pinned: (Square, Piece),
+	target: (Square, Piece),
+}
+
+impl Pin {
+
+	fn new(pinner: (Square, Piece), pinned: (Square, Piece), target: (Square, Piece)) -> Self {
+		Self {
+			pinner,
+			pinned,
+			target,
 		}
 	}
 
-	fn update_shortest_so_far(&mut self, l: usize) {
-		self.shortest_so_far = if l < self.shortest_so_far { l } else { self.shortest_so_far };
+	pub fn get_pinner(&self) -> (Square, Piece) {
+		self.pinner
 	}
-}
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-pub struct Move(Square, Square);
+	pub fn get_pinned(&self) -> (Square, Piece) {
+		self.pinned
+	}
+
+	pub fn get_target(&self) -> (Square, Piece) {
+		self.target
+	}
+
+	pub fn is_absolute(&self) -> bool {
+		self.target.1 == Piece::King
+	}
+}
 
 impl Move {
 
 	pub fn new(from: Square, to: Square) -> Self {
-		Move(from, to)
+		Move(from, to, None)
+	}
+
+	pub fn new_promotion(from: Square, to: Square, promotion: Piece) -> Self {
+		Move(from, to, Some(promotion))
+	}
+
+	pub fn get_source(&self) -> Square {
+		self.0
+	}
+
+	pub fn get_destination(&self) -> Square {
+		self.1
+	}
+
+	pub fn get_promotion(&self) -> Option<Piece> {
+		self.2
+	}
+
+	pub fn with_promotions(&self) -> Vec<Self> {
+		Piece::promotions().into_iter().map(|p| Self::new_promotion(self.0, self.1, p)).collect()
+	}
+
+	pub fn get_promotion_string(&self) -> String {
+		match self.2 {
+			None => "".to_string(),
+			Some(p) => format!("={}", p.to_string())
+		}
 	}
 
 	pub fn parse_move_strings(s: String) -> Vec<String> {
@@ -1498,6 +2471,39 @@ impl Move {
 		return v;
 	}
 
+	pub fn is_uci(s: &str) -> bool {
+		UCI_MOVE.is_match(s)
+	}
+
+	pub fn from_uci(s: &str) -> Result<Self, String> {
+		match UCI_MOVE.captures(s) {
+			None => Err(format!("{:?} is not a valid UCI move.", s)),
+			Some(characters) => {
+				let source = Square::try_parse(characters.get(1).map_or("", |m| m.as_str()))?;
+				let destination = Square::try_parse(characters.get(2).map_or("", |m| m.as_str()))?;
+				let promotion = characters.get(3).map(|m| Piece::from_str(&m.as_str().to_uppercase()));
+				Ok(Move(source, destination, promotion))
+			}
+		}
+	}
+
+	pub fn to_uci(self) -> String {
+		let promotion = match self.2 {
+			None => "".to_string(),
+			Some(p) => p.to_string().to_lowercase(),
+		};
+		format!("{}{}{}", self.0.to_string(), self.1.to_string(), promotion)
+	}
+
+	pub fn normalize_move_string(s: &str) -> String {
+		let is_annotation = |c: char| c == '+' || c == '#' || c == '!' || c == '?';
+		let s = s.trim().trim_end_matches(is_annotation);
+		let s = s.trim_end_matches("e.p.").trim_end().trim_end_matches(is_annotation);
+		let s = str::replace(s, "x", "");
+		let s = str::replace(&s, "0", "O");
+		return s;
+	}
+
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -1527,6 +2533,10 @@ impl Square {
 		let file_char = s.chars().nth(0).unwrap();
 		let rank_char = s.chars().nth(1).unwrap();
 
+		if !('a'..='h').contains(&file_char) || !('1'..='8').contains(&rank_char) {
+			return Err(format!("{:?} is not a valid square.", s));
+		}
+
 		let file = File::from_char(file_char);
 		let rank = Rank::from_char(rank_char);
 
@@ -1563,6 +2573,14 @@ impl Square {
 		Self::new(File::get_random(), Rank::get_random())
 	}
 
+	fn to_index(self) -> usize {
+		8*(self.1 as usize) + (self.0 as usize)
+	}
+
+	fn from_index(i: usize) -> Self {
+		Square(File::from_u64((i % 8) as u64), Rank::from_u64((i / 8) as u64))
+	}
+
 	pub fn to_string(&self) -> String {
 		format!("{}{}", self.0.to_string(), self.1.to_string())
 	}
@@ -1685,6 +2703,22 @@ impl Direction {
 		}
 	}
 
+	fn laterals() -> Vec<Self> {
+		vec![
+			Direction::Up,
+			Direction::Down,
+			Direction::Left,
+			Direction::Right,
+		]
+	}
+
+	fn is_towards_higher_squares(&self) -> bool {
+		match self {
+			Direction::Up | Direction::Right | Direction::UpLeft | Direction::UpRight => true,
+			Direction::Down | Direction::Left | Direction::DownLeft | Direction::DownRight => false
+		}
+	}
+
 	fn get_forward_diagonals(side: Side) -> (Self, Self) {
 		match side {
 			Side::White => (Direction::UpLeft, Direction::UpRight),
@@ -1924,6 +2958,13 @@ impl Rank {
 			Side::Black => Rank::Seven
 		}
 	}
+
+	pub fn promotion(side: Side) -> Self {
+		match side {
+			Side::White => Rank::Eight,
+			Side::Black => Rank::One
+		}
+	}
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -1940,26 +2981,79 @@ impl Bitboard {
 	}
 
 	pub fn square(square: Square) -> Self {
-		Self::file(square.0) & Self::rank(square.1)
+		Bitboard(1 << square.to_index())
 	}
 
 	pub fn to_squares(&self) -> Vec<Square> {
 		let mut to_return = Vec::new();
 		for file in File::all() {
-			for rank in Rank::all() {
-				let square = Square::new(file, rank);
-				if (&Self::square(square) & self).has_pieces() {
-					to_return.push(square);
-				}
+			let mut remaining = (self & &Self::file(file)).0;
+			while remaining != 0 {
+				to_return.push(Square::from_index(remaining.trailing_zeros() as usize));
+				remaining &= remaining - 1;
 			}
 		}
 		return to_return;
 	}
 
+	pub fn count(&self) -> usize {
+		self.0.count_ones() as usize
+	}
+
+	pub fn knight_attacks(square: Square) -> Self {
+		KNIGHT_ATTACKS[square.to_index()]
+	}
+
+	pub fn king_attacks(square: Square) -> Self {
+		KING_ATTACKS[square.to_index()]
+	}
+
+	pub fn pawn_attacks(side: Side, square: Square) -> Self {
+		PAWN_ATTACKS[side as usize][square.to_index()]
+	}
+
+	pub fn diagonal_attacks(square: Square, occupancy: Bitboard) -> Self {
+		Direction::diagonals().into_iter().fold(Self::empty(), |accumulator, d| accumulator | Self::ray_attacks(square, d, occupancy))
+	}
+
+	pub fn lateral_attacks(square: Square, occupancy: Bitboard) -> Self {
+		Direction::laterals().into_iter().fold(Self::empty(), |accumulator, d| accumulator | Self::ray_attacks(square, d, occupancy))
+	}
+
+	fn ray_attacks(square: Square, direction: Direction, occupancy: Bitboard) -> Self {
+		let rays = &RAYS[direction as usize];
+		let ray = rays[square.to_index()];
+		let blockers = (ray & occupancy).0;
+		if blockers == 0 {
+			return ray;
+		}
+		let nearest = match direction.is_towards_higher_squares() {
+			true => blockers.trailing_zeros() as usize,
+			false => 63 - (blockers.leading_zeros() as usize)
+		};
+		return ray & rays[nearest].get_inverse();
+	}
+
+	fn shift_forward(&self, side: Side) -> Self {
+		match side {
+			Side::White => Bitboard(self.0 << 8),
+			Side::Black => Bitboard(self.0 >> 8)
+		}
+	}
+
+	fn precompute<F: Fn(Square) -> Bitboard>(f: F) -> Vec<Self> {
+		(0..64).map(|i| f(Square::from_index(i))).collect()
+	}
+
 	pub fn rank(rank: Rank) -> Self {
 		Bitboard(255 << (8*(rank as u64))) // 255 = 1+2+4+8+16+32+64+128 = 2**8 - 1
 	}
 
+	fn between_on_rank(from: Square, to: Square) -> Self {
+		let (low, high) = if (from.0 as u64) < (to.0 as u64) { (from.0 as u64, to.0 as u64) } else { (to.0 as u64, from.0 as u64) };
+		Bitboard::from_squares((low..=high).map(|f| Square::new(File::from_u64(f), from.1)).collect())
+	}
+
 	pub fn file(file: File) -> Self {
 		Bitboard(72340172838076673 << (file as u64)) // 72340172838076673 = 1+2**8+2**16+2**24+2**32+2**40+2**48+2**56
 	}
@@ -1977,7 +3071,7 @@ impl Bitboard {
 	}
 
 	pub fn contains(&self, square: Square) -> bool {
-		(&Self::square(square) & self).0 > 0
+		(self.0 >> square.to_index()) & 1 == 1
 	}
 
 	pub fn get_inverse(&self) -> Self {
@@ -2134,7 +3228,7 @@ mod tests {
 		let mut board = Board::empty();
 		board.add(Side::Black, Piece::Pawn, Square::from_string("f5"));
 		let moves = board.get_legal_moves(Square::from_string("f5"));
-		assert_eq!(moves[0], Move(Square::from_string("f5"), Square::from_string("f4")), "{:?}", moves);
+		assert_eq!(moves[0], Move::new(Square::from_string("f5"), Square::from_string("f4")), "{:?}", moves);
 
 		board.add(Side::White, Piece::Queen, Square::from_string("e4"));
 		board.add(Side::White, Piece::Knight, Square::from_string("g4"));
@@ -2144,17 +3238,17 @@ mod tests {
 
 		let moves = board.get_legal_moves(Square::from_string("f5"));
 		assert_eq!(moves.len(), 3, "{:?}", moves);
-		assert_eq!(moves[0], Move(Square::from_string("f5"), Square::from_string("e4")), "{:?}", moves);
-		assert_eq!(moves[1], Move(Square::from_string("f5"), Square::from_string("f4")), "{:?}", moves);
-		assert_eq!(moves[2], Move(Square::from_string("f5"), Square::from_string("g4")), "{:?}", moves);
+		assert_eq!(moves[0], Move::new(Square::from_string("f5"), Square::from_string("e4")), "{:?}", moves);
+		assert_eq!(moves[1], Move::new(Square::from_string("f5"), Square::from_string("f4")), "{:?}", moves);
+		assert_eq!(moves[2], Move::new(Square::from_string("f5"), Square::from_string("g4")), "{:?}", moves);
 
 		board.add(Side::White, Piece::Pawn, Square::from_string("a2"));
 		let moves = board.get_legal_moves(Square::from_string("a2"));
 		assert_eq!(
 			moves,
 			vec![
-				Move(Square::from_string("a2"), Square::from_string("a3")),
-				Move(Square::from_string("a2"), Square::from_string("a4")),
+				Move::new(Square::from_string("a2"), Square::from_string("a3")),
+				Move::new(Square::from_string("a2"), Square::from_string("a4")),
 			]
 		);
 
@@ -2165,6 +3259,462 @@ mod tests {
 		assert!(!board.get_legal_moves_for_side(Side::White).contains(&m));
 	}
 
+	#[test]
+	fn board_promotes_pawns() {
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("a1"));
+		board.add(Side::Black, Piece::King, Square::from_string("h8"));
+		board.add(Side::White, Piece::Pawn, Square::from_string("e7"));
+		board.add(Side::Black, Piece::Rook, Square::from_string("d8"));
+
+		let e7 = Square::from_string("e7");
+		let e8 = Square::from_string("e8");
+		let d8 = Square::from_string("d8");
+		let moves = board.get_legal_moves(e7);
+		assert_eq!(
+			moves,
+			vec![
+				Move::new_promotion(e7, d8, Piece::Queen),
+				Move::new_promotion(e7, d8, Piece::Rook),
+				Move::new_promotion(e7, d8, Piece::Bishop),
+				Move::new_promotion(e7, d8, Piece::Knight),
+				Move::new_promotion(e7, e8, Piece::Queen),
+				Move::new_promotion(e7, e8, Piece::Rook),
+				Move::new_promotion(e7, e8, Piece::Bishop),
+				Move::new_promotion(e7, e8, Piece::Knight),
+			]
+		);
+		assert!(!board.is_legal_move(Move::new(e7, e8)));
+
+		assert_eq!(board.try_parse_move(Side::White, "e8=Q"), Ok(Move::new_promotion(e7, e8, Piece::Queen)));
+		assert_eq!(board.try_parse_move(Side::White, "e8R"), Ok(Move::new_promotion(e7, e8, Piece::Rook)));
+		assert_eq!(board.try_parse_move(Side::White, "exd8=N"), Ok(Move::new_promotion(e7, d8, Piece::Knight)));
+		assert_eq!(board.try_parse_move(Side::White, "ed8=B"), Ok(Move::new_promotion(e7, d8, Piece::Bishop)));
+		assert!(board.try_parse_move(Side::White, "e8").is_err());
+		assert!(board.try_parse_move(Side::White, "e8=K").is_err());
+
+		assert_eq!(board.get_move_string(Move::new_promotion(e7, e8, Piece::Queen)), "e8=Q+");
+		assert_eq!(board.get_move_string(Move::new_promotion(e7, d8, Piece::Knight)), "exd8=N");
+
+		board.make_move(Move::new_promotion(e7, d8, Piece::Knight));
+		assert_eq!(board.get(d8), Some((Side::White, Piece::Knight)));
+		assert_eq!(board.get(e7), None);
+		assert_eq!(board.pawns, Bitboard::empty());
+		assert_eq!(board.rooks, Bitboard::empty());
+	}
+
+	#[test]
+	fn board_captures_en_passant() {
+		let mut board = Board::starting_position();
+		board.make_move(board.force_parse_move(Side::White, "e4"));
+		assert_eq!(board.get_en_passant(), Some(Square::from_string("e3")));
+		board.make_move(board.force_parse_move(Side::Black, "a6"));
+		assert_eq!(board.get_en_passant(), None);
+		board.make_move(board.force_parse_move(Side::White, "e5"));
+		board.make_move(board.force_parse_move(Side::Black, "d5"));
+		assert_eq!(board.fen(Side::White, 0, 3), "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+
+		let m = board.force_parse_move(Side::White, "exd6");
+		assert_eq!(m, Move::new(Square::from_string("e5"), Square::from_string("d6")));
+		assert!(board.get_captures(Side::White).contains(&m));
+		assert!(board.try_parse_move(Side::White, "ef6").is_err());
+
+		board.make_move(m);
+		assert_eq!(board.get(Square::from_string("d6")), Some((Side::White, Piece::Pawn)));
+		assert_eq!(board.get(Square::from_string("d5")), None);
+		assert_eq!(board.get_en_passant(), None);
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("a5"));
+		board.add(Side::White, Piece::Pawn, Square::from_string("b5"));
+		board.add(Side::Black, Piece::Pawn, Square::from_string("c7"));
+		board.add(Side::Black, Piece::Rook, Square::from_string("h5"));
+		board.add(Side::Black, Piece::King, Square::from_string("h8"));
+		board.make_move(board.force_parse_move(Side::Black, "c5"));
+		assert!(!board.is_legal_move(Move::new(Square::from_string("b5"), Square::from_string("c6"))));
+	}
+
+	#[test]
+	fn board_gets_standard_move_strings() {
+		let mut board = Board::starting_position();
+		board.make_move(board.force_parse_move(Side::White, "e4"));
+		board.make_move(board.force_parse_move(Side::Black, "d5"));
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "exd5")), "exd5");
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "Bb5")), "Bb5+");
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::White, Piece::Rook, Square::from_string("h1"));
+		board.add(Side::White, Piece::Queen, Square::from_string("b6"));
+		board.add(Side::Black, Piece::King, Square::from_string("f8"));
+		board.add(Side::Black, Piece::Pawn, Square::from_string("b7"));
+		board.add(Side::Black, Piece::Pawn, Square::from_string("f7"));
+		board.add(Side::Black, Piece::Pawn, Square::from_string("g7"));
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "O-O")), "O-O");
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "Qxb7")), "Qxb7");
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "Qd8")), "Qd8#");
+		assert_eq!(board.force_parse_move(Side::White, "Qd8#"), board.force_parse_move(Side::White, "Qd8"));
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::White, Piece::Rook, Square::from_string("a1"));
+		board.add(Side::Black, Piece::King, Square::from_string("d5"));
+		assert_eq!(board.get_move_string(board.force_parse_move(Side::White, "O-O-O")), "O-O-O+");
+	}
+
+	#[test]
+	fn board_disambiguates_moves() {
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("h1"));
+		board.add(Side::Black, Piece::King, Square::from_string("g8"));
+		board.add(Side::White, Piece::Queen, Square::from_string("a1"));
+		board.add(Side::White, Piece::Queen, Square::from_string("a3"));
+		board.add(Side::White, Piece::Queen, Square::from_string("c1"));
+		board.add(Side::White, Piece::Knight, Square::from_string("d1"));
+		board.add(Side::White, Piece::Knight, Square::from_string("d5"));
+
+		let b2 = Square::from_string("b2");
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("a1"), b2)), "Qa1b2");
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("a3"), b2)), "Q3b2");
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("c1"), b2)), "Qcb2");
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("d1"), Square::from_string("c3"))), "N1c3");
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("d5"), Square::from_string("e3"))), "N5e3");
+
+		assert_eq!(board.try_parse_move(Side::White, "Qa1b2"), Ok(Move::new(Square::from_string("a1"), b2)));
+		assert_eq!(board.try_parse_move(Side::White, "Qa1xb2"), Ok(Move::new(Square::from_string("a1"), b2)));
+		assert_eq!(board.try_parse_move(Side::White, "Q3b2"), Ok(Move::new(Square::from_string("a3"), b2)));
+		assert_eq!(board.try_parse_move(Side::White, "Qcb2"), Ok(Move::new(Square::from_string("c1"), b2)));
+		assert!(board.try_parse_move(Side::White, "Qab2").is_err());
+		assert!(board.try_parse_move(Side::White, "Q1b2").is_err());
+		assert!(board.try_parse_move(Side::White, "Qb2").is_err());
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::White, Piece::Knight, Square::from_string("e2"));
+		board.add(Side::White, Piece::Knight, Square::from_string("g1"));
+		board.add(Side::Black, Piece::King, Square::from_string("h8"));
+		board.add(Side::Black, Piece::Rook, Square::from_string("e8"));
+		assert_eq!(board.try_parse_move(Side::White, "Nf3"), Ok(Move::new(Square::from_string("g1"), Square::from_string("f3"))));
+		assert_eq!(board.get_move_string(Move::new(Square::from_string("g1"), Square::from_string("f3"))), "Nf3");
+	}
+
+	#[test]
+	fn board_parses_uci_moves() {
+		let e7 = Square::from_string("e7");
+		let e8 = Square::from_string("e8");
+		assert_eq!(Move::from_uci("e7e8q"), Ok(Move::new_promotion(e7, e8, Piece::Queen)));
+		assert_eq!(Move::from_uci("e7e8N"), Ok(Move::new_promotion(e7, e8, Piece::Knight)));
+		assert_eq!(Move::from_uci("g1f3"), Ok(Move::new(Square::from_string("g1"), Square::from_string("f3"))));
+		assert!(Move::from_uci("g1f9").is_err());
+		assert!(Move::from_uci("Nf3").is_err());
+		assert_eq!(Move::new_promotion(e7, e8, Piece::Rook).to_uci(), "e7e8r");
+		assert_eq!(Move::new(Square::from_string("e1"), Square::from_string("g1")).to_uci(), "e1g1");
+
+		let board = Board::starting_position();
+		assert_eq!(board.try_parse_move(Side::White, "g1f3"), board.try_parse_move(Side::White, "Nf3"));
+		assert_eq!(board.try_parse_move(Side::Black, "e7e5"), board.try_parse_move(Side::Black, "e5"));
+		assert!(board.try_parse_move(Side::White, "g1g3").is_err());
+		assert!(board.try_parse_move(Side::White, "e7e5").is_err());
+		assert!(board.try_parse_move(Side::White, "e3e4").is_err());
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("a1"));
+		board.add(Side::Black, Piece::King, Square::from_string("h1"));
+		board.add(Side::White, Piece::Pawn, e7);
+		assert!(board.try_parse_move(Side::White, "e7e8").is_err());
+		assert_eq!(board.try_parse_move(Side::White, "e7e8q"), Ok(Move::new_promotion(e7, e8, Piece::Queen)));
+	}
+
+	#[test]
+	fn board_detects_insufficient_material() {
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::Black, Piece::King, Square::from_string("e8"));
+		assert!(board.is_insufficient_material());
+		board.add(Side::White, Piece::Knight, Square::from_string("b1"));
+		assert!(board.is_insufficient_material());
+		board.add(Side::Black, Piece::Bishop, Square::from_string("c8"));
+		assert!(!board.is_insufficient_material());
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::Black, Piece::King, Square::from_string("e8"));
+		board.add(Side::White, Piece::Bishop, Square::from_string("c1"));
+		board.add(Side::Black, Piece::Bishop, Square::from_string("f8"));
+		assert!(board.is_insufficient_material());
+		board.add(Side::Black, Piece::Bishop, Square::from_string("c8"));
+		assert!(!board.is_insufficient_material());
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::Black, Piece::King, Square::from_string("e8"));
+		board.add(Side::White, Piece::Pawn, Square::from_string("a2"));
+		assert!(!board.is_insufficient_material());
+		assert!(!Board::starting_position().is_insufficient_material());
+	}
+
+	fn assert_perft(fen: &str, expected: Vec<usize>) {
+		let (board, side, _, _) = Board::from_fen(fen).unwrap();
+		for (depth, nodes) in expected.into_iter().enumerate() {
+			assert_eq!(board.perft(side, depth + 1), nodes, "Perft({}) failed for {}", depth + 1, fen);
+		}
+	}
+
+	#[test]
+	fn board_unmakes_moves() {
+		let fens = vec![
+			"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+			"r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+			"rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+		];
+		for fen in fens {
+			let (mut board, side, _, _) = Board::from_fen(fen).unwrap();
+			let original = board.clone();
+			for m in original.get_legal_moves_for_side(side) {
+				let undo = board.force_make_move(m);
+				assert_eq!(board, original.get_transformation(m));
+				for reply in board.clone().get_legal_moves_for_side(Side::get_opponent(side)) {
+					let reply_undo = board.force_make_move(reply);
+					board.unmake_move(reply_undo);
+				}
+				board.unmake_move(undo);
+				assert_eq!(board, original, "Unmaking {:?} didn't restore {}", m, fen);
+			}
+		}
+
+		let (mut board, _, _, _) = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+		let undo = board.make_move(Move::new(Square::from_string("e5"), Square::from_string("f6")));
+		assert_eq!(undo.get_captured(), Some((Square::from_string("f5"), Piece::Pawn)));
+		assert_eq!(board.get(Square::from_string("f5")), None);
+		board.unmake_move(undo);
+		assert_eq!(board.get(Square::from_string("f5")), Some((Side::Black, Piece::Pawn)));
+		assert_eq!(board.get_en_passant(), Some(Square::from_string("f6")));
+	}
+
+	#[test]
+	fn board_maintains_zobrist_keys() {
+		let start = Board::starting_position();
+		assert_eq!(start.zobrist, start.compute_zobrist());
+		assert_ne!(start.get_zobrist_key(Side::White), start.get_zobrist_key(Side::Black));
+
+		let (mut board, side, _, _) = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+		assert_eq!(board.zobrist, board.compute_zobrist());
+		let original = board.clone();
+		for m in original.get_legal_moves_for_side(side) {
+			let undo = board.force_make_move(m);
+			assert_eq!(board.zobrist, board.compute_zobrist(), "{:?}", m);
+			board.unmake_move(undo);
+			assert_eq!(board.zobrist, original.zobrist, "{:?}", m);
+		}
+
+		let mut transposed = Board::starting_position();
+		let mut other = Board::starting_position();
+		for m in ["g1f3", "g8f6", "b1c3"] {
+			transposed.make_move(Move::from_uci(m).unwrap());
+		}
+		for m in ["b1c3", "g8f6", "g1f3"] {
+			other.make_move(Move::from_uci(m).unwrap());
+		}
+		assert_eq!(transposed.get_zobrist_key(Side::Black), other.get_zobrist_key(Side::Black));
+		let positions: HashSet<Board> = vec![transposed, other, start].into_iter().collect();
+		assert_eq!(positions.len(), 2);
+
+		let (with_target, _, _, _) = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+		let (without_target, _, _, _) = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+		assert_ne!(with_target.zobrist, without_target.zobrist);
+		assert_eq!(with_target.get_zobrist_key(Side::Black), without_target.get_zobrist_key(Side::Black));
+	}
+
+	#[test]
+	fn board_passes_perft() {
+		assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", vec![20, 400, 8902]);
+		assert_perft("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", vec![48, 2039, 97862]);
+		assert_perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", vec![14, 191, 2812, 43238]);
+		assert_perft("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", vec![6, 264, 9467]);
+		assert_perft("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", vec![44, 1486, 62379]);
+		assert_perft("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", vec![46, 2079, 89890]);
+
+		let board = Board::starting_position();
+		let divide = board.divide(Side::White, 2);
+		assert_eq!(divide.len(), 20);
+		assert!(divide.into_iter().all(|(_, n)| n == 20));
+	}
+
+	#[test]
+	fn board_generates_chess960_positions() {
+		assert_eq!(Board::chess960_position(518).unwrap(), Board::starting_position());
+		assert_eq!(Board::starting_position().get_chess960_number(), Some(518));
+		assert!(Board::chess960_position(CHESS960_POSITIONS).is_err());
+
+		let fens: HashSet<String> = (0..CHESS960_POSITIONS).map(|n| Board::chess960_position(n).unwrap().fen(Side::White, 0, 1)).collect();
+		assert_eq!(fens.len(), CHESS960_POSITIONS);
+
+		let board = Board::chess960_position(0).unwrap();
+		assert!(board.is_chess960());
+		assert_eq!(board.get_chess960_number(), Some(0));
+		assert_eq!(board.fen(Side::White, 0, 1), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+		assert_eq!(board.shredder_fen(Side::White, 0, 1), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1");
+		assert_eq!(Board::starting_position().shredder_fen(Side::White, 0, 1), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1");
+	}
+
+	#[test]
+	fn board_castles_in_chess960() {
+		assert_perft("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", vec![21, 528, 12189]);
+		assert_perft("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", vec![21, 807, 18002]);
+		assert_perft("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", vec![20, 479, 10471]);
+
+		let (mut board, _, _, _) = Board::from_fen("1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w GBgb - 0 1").unwrap();
+		assert!(board.is_chess960());
+		let original = board.clone();
+		let castle = board.try_parse_move(Side::White, "O-O").unwrap();
+		assert_eq!(castle, Move::from_uci("e1g1").unwrap());
+		assert_eq!(board.get_move_string(castle), "O-O");
+		let undo = board.make_move(castle);
+		assert_eq!(board.get(Square::from_string("g1")), Some((Side::White, Piece::King)));
+		assert_eq!(board.get(Square::from_string("f1")), Some((Side::White, Piece::Rook)));
+		assert_eq!(board.fen(Side::Black, 1, 1), "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b kq - 1 1");
+		assert_eq!(board.shredder_fen(Side::Black, 1, 1), "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b gb - 1 1");
+		board.unmake_move(undo);
+		assert_eq!(board, original);
+
+		let castle = board.try_parse_move(Side::Black, "O-O-O").unwrap();
+		assert_eq!(castle, Move::from_uci("e8b8").unwrap());
+		board.make_move(castle);
+		assert_eq!(board.get(Square::from_string("c8")), Some((Side::Black, Piece::King)));
+		assert_eq!(board.get(Square::from_string("d8")), Some((Side::Black, Piece::Rook)));
+
+		let (mut board, _, _, _) = Board::from_fen("4k3/8/8/8/8/8/8/4KR2 w K - 0 1").unwrap();
+		assert_eq!(board.fen(Side::White, 0, 1), "4k3/8/8/8/8/8/8/4KR2 w K - 0 1");
+		board.make_move(board.try_parse_move(Side::White, "O-O").unwrap());
+		assert_eq!(board.fen(Side::Black, 1, 1), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+	}
+
+	#[test]
+	fn board_detects_mates() {
+		let (board, _, _, _) = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+		assert_eq!(board.get_mating_moves(Side::White), vec![Move::from_uci("a1a8").unwrap()]);
+		assert!(board.get_mating_moves(Side::Black).is_empty());
+		assert!(Board::starting_position().get_mating_moves(Side::White).is_empty());
+	}
+
+	#[test]
+	fn board_detects_threats() {
+		let (board, _, _, _) = Board::from_fen("4k3/8/8/3n4/8/8/8/R3K2Q b - - 0 1").unwrap();
+		let threats: HashSet<Move> = board.get_threats(Side::Black).into_iter().collect();
+		let expected: HashSet<Move> = vec!["a1a8", "h1h8", "h1h5", "h1e4", "h1d5"].into_iter().map(|m| Move::from_uci(m).unwrap()).collect();
+		assert_eq!(threats, expected);
+		assert!(board.get_threats(Side::White).is_empty());
+		assert!(Board::starting_position().get_threats(Side::White).is_empty());
+
+		let (board, _, _, _) = Board::from_fen("4k3/8/4p3/3n4/8/8/8/4K2Q b - - 0 1").unwrap();
+		assert_eq!(board.get_capture_exchange_evaluation(Move::from_uci("h1d5").unwrap()), -6);
+		assert!(!board.get_threats(Side::Black).contains(&Move::from_uci("h1d5").unwrap()));
+	}
+
+	#[test]
+	fn board_classifies_checks() {
+		let (board, _, _, _) = Board::from_fen("4k3/8/8/8/4N3/3B4/8/4RK2 w - - 0 1").unwrap();
+		let m = |s: &str| Move::from_uci(s).unwrap();
+		assert_eq!(board.get_check_type(m("e4c5")), Some(CheckType::Discovered));
+		assert_eq!(board.get_unmasked_checkers(m("e4c5")), vec![(Square::from_string("e1"), Piece::Rook)]);
+		assert_eq!(board.get_check_type(m("e4d6")), Some(CheckType::Double));
+		assert_eq!(board.get_check_type(m("e4f6")), Some(CheckType::Double));
+		assert_eq!(board.get_check_type(m("d3b5")), Some(CheckType::Direct));
+		assert!(board.get_unmasked_checkers(m("d3b5")).is_empty());
+		assert_eq!(board.get_check_type(m("e1a1")), None);
+
+		let discovered: HashSet<Move> = board.get_discovered_checks(Side::White).into_iter().collect();
+		let expected: HashSet<Move> = vec!["e4c3", "e4c5", "e4d2", "e4f2", "e4g3", "e4g5", "e4d6", "e4f6"].into_iter().map(m).collect();
+		assert_eq!(discovered, expected);
+		assert!(Board::starting_position().get_discovered_checks(Side::White).is_empty());
+	}
+
+	#[test]
+	fn board_finds_forced_mates() {
+		let (board, _, _, _) = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+		assert_eq!(board.get_mate_in(Side::White, 2), Some(1));
+
+		let (board, _, _, _) = Board::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+		assert_eq!(board.get_mate_in(Side::White, 1), None);
+		assert_eq!(board.get_mate_in(Side::White, 2), Some(2));
+		let mates = board.get_forced_mate_moves(Side::White, 2);
+		assert!(mates.contains(&Move::from_uci("a2a7").unwrap()));
+		assert!(mates.contains(&Move::from_uci("b1b7").unwrap()));
+		assert!(!mates.contains(&Move::from_uci("a2a8").unwrap()));
+
+		let line = |s: &str| s.split(", ").map(|m| Move::from_uci(m).unwrap()).collect::<Vec<Move>>();
+		assert!(board.validate_forced_mate_line(Side::White, 2, line("a2a7, h8g8, b1b8")).is_ok());
+		assert!(board.validate_forced_mate_line(Side::White, 2, line("a2a8, h8g7, b1b7")).is_err());
+		assert!(board.validate_forced_mate_line(Side::White, 2, line("a2a7, h8g8")).is_err());
+		assert!(board.validate_forced_mate_line(Side::White, 0, line("a2a7, h8g8, b1b8")).is_err());
+		assert_eq!(board.get_mate_in(Side::White, 0), None);
+		assert_eq!(board.get_mate_in_by_checks(Side::White, 0), None);
+		assert!(board.get_forced_mate_moves(Side::White, 0).is_empty());
+
+		let (board, _, _, _) = Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+		assert_eq!(board.get_mate_in(Side::White, 3), Some(1));
+		assert_eq!(Board::starting_position().get_mate_in(Side::White, 2), None);
+	}
+
+	#[test]
+	fn board_detects_forks() {
+		let (board, _, _, _) = Board::from_fen("r3k3/8/2n1b3/1N6/3P4/8/8/4K3 w - - 0 1").unwrap();
+		let forks: HashSet<Move> = board.get_forking_moves(Side::White).into_iter().collect();
+		let expected: HashSet<Move> = vec!["b5c7", "d4d5"].into_iter().map(|m| Move::from_uci(m).unwrap()).collect();
+		assert_eq!(forks, expected);
+		assert!(Board::starting_position().get_forking_moves(Side::White).is_empty());
+	}
+
+	#[test]
+	fn board_counts_xray_defenders() {
+		let e5 = Square::from_string("e5");
+		let (board, _, _, _) = Board::from_fen("8/7k/8/8/8/2Q5/1B2R3/R3Q1K1 w - - 0 1").unwrap();
+		assert_eq!(board.get_n_defenders(Side::White, e5), 2);
+		assert_eq!(board.get_n_xray_defenders(Side::White, e5), 4);
+		assert_eq!(board.get_n_attackers(Side::Black, e5), 2);
+		assert_eq!(board.get_n_xray_attackers(Side::Black, e5), 4);
+		assert_eq!(board.get_n_xray_defenders(Side::Black, e5), 0);
+	}
+
+	#[test]
+	fn board_computes_static_exchanges() {
+		let e5 = Square::from_string("e5");
+		let (board, _, _, _) = Board::from_fen("4k3/8/8/4n3/8/8/8/4RK2 w - - 0 1").unwrap();
+		assert_eq!(board.get_static_exchange_evaluation(Side::White, e5), 3);
+		assert_eq!(board.get_hanging_pieces(Side::Black), vec![e5]);
+
+		let (board, _, _, _) = Board::from_fen("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1").unwrap();
+		assert_eq!(board.get_static_exchange_evaluation(Side::White, e5), -4);
+		assert!(board.get_hanging_pieces(Side::Black).is_empty());
+
+		let (board, _, _, _) = Board::from_fen("k3r3/8/8/4p3/8/8/4R3/4Q1K1 w - - 0 1").unwrap();
+		assert_eq!(board.get_static_exchange_evaluation(Side::White, e5), 1);
+		assert_eq!(board.get_hanging_pieces(Side::Black), vec![e5]);
+		assert!(board.get_hanging_pieces(Side::White).is_empty());
+		assert_eq!(board.get_static_exchange_evaluation(Side::Black, Square::from_string("e2")), 0);
+	}
+
+	#[test]
+	fn board_detects_pins() {
+		let (board, _, _, _) = Board::from_fen("4k3/4r3/7b/1b6/B4N2/3N4/3QN3/4K3 w - - 0 1").unwrap();
+		let sq = |s| Square::from_string(s);
+
+		let white_pins: HashSet<Pin> = board.get_pins(Side::White).into_iter().collect();
+		let expected: HashSet<Pin> = vec![
+			Pin::new((sq("e7"), Piece::Rook), (sq("e2"), Piece::Knight), (sq("e1"), Piece::King)),
+			Pin::new((sq("h6"), Piece::Bishop), (sq("f4"), Piece::Knight), (sq("d2"), Piece::Queen)),
+		].into_iter().collect();
+		assert_eq!(white_pins, expected);
+		assert_eq!(white_pins.iter().filter(|p| p.is_absolute()).count(), 1);
+
+		let black_pins = board.get_pins(Side::Black);
+		assert_eq!(black_pins, vec![Pin::new((sq("a4"), Piece::Bishop), (sq("b5"), Piece::Bishop), (sq("e8"), Piece::King))]);
+		assert!(Board::starting_position().get_pins(Side::White).is_empty());
+
+		let (board, _, _, _) = Board::from_fen("4k3/8/8/6r1/1b6/8/3B2N1/4N1NK w - - 0 1").unwrap();
+		assert!(board.get_pins(Side::White).is_empty());
+	}
+
 	#[test]
 	fn board_computes_castles() {
 		let mut board = Board::starting_position();
@@ -2232,6 +3782,42 @@ mod tests {
 
 	}
 
+	#[test]
+	fn board_revokes_castling_rights() {
+		let mut board = Board::starting_position();
+		board.make_move(board.force_parse_move(Side::White, "e4"));
+		board.make_move(board.force_parse_move(Side::Black, "e5"));
+		board.make_move(board.force_parse_move(Side::White, "Nf3"));
+		board.make_move(board.force_parse_move(Side::Black, "Nf6"));
+		board.make_move(board.force_parse_move(Side::White, "Bc4"));
+		board.make_move(board.force_parse_move(Side::Black, "Bc5"));
+		assert_eq!(board.get_castles(Side::White), vec![Castle::Kingside]);
+		assert_eq!(board.get_castles(Side::Black), vec![Castle::Kingside]);
+
+		board.make_move(board.force_parse_move(Side::White, "Ke2"));
+		board.make_move(board.force_parse_move(Side::Black, "Rg8"));
+		board.make_move(board.force_parse_move(Side::White, "Ke1"));
+		board.make_move(board.force_parse_move(Side::Black, "Rh8"));
+
+		assert!(board.try_parse_move(Side::White, "O-O").is_err());
+		assert!(board.try_parse_move(Side::Black, "O-O").is_err());
+		assert!(!board.get_castling_rights(Side::White, Castle::Kingside));
+		assert!(!board.get_castling_rights(Side::White, Castle::Queenside));
+		assert!(!board.get_castling_rights(Side::Black, Castle::Kingside));
+		assert!(board.get_castling_rights(Side::Black, Castle::Queenside));
+		assert_eq!(board.fen(Side::White, 4, 7), "rnbqk2r/pppp1ppp/5n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w q - 4 7");
+
+		let mut board = Board::empty();
+		board.add(Side::White, Piece::King, Square::from_string("e1"));
+		board.add(Side::White, Piece::Rook, Square::from_string("a1"));
+		board.add(Side::White, Piece::Rook, Square::from_string("h1"));
+		board.add(Side::Black, Piece::King, Square::from_string("e8"));
+		board.add(Side::Black, Piece::Bishop, Square::from_string("a8"));
+		board.make_move(board.force_parse_move(Side::Black, "Bh1"));
+		assert_eq!(board.get_castles(Side::White), vec![Castle::Queenside]);
+		assert!(!board.get_castling_rights(Side::White, Castle::Kingside));
+	}
+
 	#[test]
 	fn board_makes_moves() {
 		let mut board = Board::starting_position();
@@ -2426,6 +4012,36 @@ mod tests {
 		
 	}
 
+	#[test]
+	fn test_board_parses_fen() {
+		let (board, side, half_moves, full_moves) = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+		assert_eq!(board, Board::starting_position());
+		assert_eq!((side, half_moves, full_moves), (Side::White, 0, 1));
+
+		let fen = "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3";
+		let (board, side, half_moves, full_moves) = Board::from_fen(fen).unwrap();
+		assert_eq!(board.get_en_passant(), Some(Square::from_string("d6")));
+		assert!(board.get_castling_rights(Side::White, Castle::Kingside));
+		assert!(!board.get_castling_rights(Side::White, Castle::Queenside));
+		assert!(!board.get_castling_rights(Side::Black, Castle::Kingside));
+		assert!(board.get_castling_rights(Side::Black, Castle::Queenside));
+		assert_eq!(board.fen(side, half_moves, full_moves), fen);
+
+		let (_board, side, half_moves, full_moves) = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+		assert_eq!((side, half_moves, full_moves), (Side::Black, 0, 1));
+
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1").is_err());
+		assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - a 1").is_err());
+		assert!(Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+		assert!(Board::from_fen("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1").is_err());
+	}
+
 	#[test]
 	fn bitboard_instantiates_square() {
 		assert_eq!(Bitboard::square(Square::new(File::A, Rank::One)).0, 1);
@@ -2454,6 +4070,28 @@ mod tests {
 		assert_eq!(bitboard, Bitboard::square(h1));
 	}
 
+	#[test]
+	fn bitboard_computes_attacks() {
+		assert_eq!(Bitboard::knight_attacks(Square::from_string("a1")).count(), 2);
+		assert_eq!(Bitboard::knight_attacks(Square::from_string("d4")).count(), 8);
+		assert_eq!(Bitboard::king_attacks(Square::from_string("h8")).count(), 3);
+		assert_eq!(Bitboard::pawn_attacks(Side::White, Square::from_string("a2")), Bitboard::square(Square::from_string("b3")));
+		assert_eq!(Bitboard::pawn_attacks(Side::Black, Square::from_string("e5")), Bitboard::from_squares(vec![Square::from_string("d4"), Square::from_string("f4")]));
+		assert_eq!(Bitboard::lateral_attacks(Square::from_string("d4"), Bitboard::empty()).count(), 14);
+
+		let occupancy = Bitboard::from_squares(vec![Square::from_string("d6"), Square::from_string("b4"), Square::from_string("f6")]);
+		let laterals = Bitboard::lateral_attacks(Square::from_string("d4"), occupancy);
+		assert_eq!(laterals.count(), 11);
+		assert!(laterals.contains(Square::from_string("d6")) && !laterals.contains(Square::from_string("d7")));
+		assert!(laterals.contains(Square::from_string("b4")) && !laterals.contains(Square::from_string("a4")));
+		let diagonals = Bitboard::diagonal_attacks(Square::from_string("d4"), occupancy);
+		assert_eq!(diagonals.count(), 11);
+		assert!(diagonals.contains(Square::from_string("f6")) && !diagonals.contains(Square::from_string("g7")));
+
+		let (board, _, _, _) = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+		assert_eq!(board.get_legal_moves(Square::from_string("b5")), vec![Move::new(Square::from_string("b5"), Square::from_string("b6"))]);
+	}
+
 	#[test]
 	fn bitboard_to_squares() {
 		let squares = vec![Square::from_string("b7"), Square::from_string("h2")];
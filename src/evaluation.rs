@@ -13,7 +13,8 @@ impl Evaluator {
     }
 
     pub fn evaluate_from_fen(fen: String) -> Result<i32, String> {
-        PlecoBoard::from_fen(&fen).map_or(Err("Invalid FEN!".to_string()), |f| Ok(Eval::eval_low(&f)))
+        let (board, side, half_moves, full_moves) = Board::from_fen(&fen)?;
+        Ok(Eval::eval_low(&Self::from(&board, side, half_moves, full_moves)))
     }
 
     fn from(board: &Board, side: Side, half_moves: usize, full_moves: usize) -> PlecoBoard {
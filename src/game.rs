@@ -1,45 +1,254 @@
-use rand::{seq::IteratorRandom, thread_rng};
+use std::fmt;
+use std::cmp::Reverse;
+use rand::thread_rng;
 use rand::rngs::ThreadRng;
 use rand::prelude::*;
-use crate::board::{Board, Move, Square, Side, Rank, File, Piece};
+use crate::board::{Board, Move, Square, Side, Rank, File, Piece, Undo, CHESS960_STANDARD_POSITION};
+use crate::pgn::{Pgn};
 
+const PGN_LINE_LENGTH: usize = 80;
+const MIN_MOVE_COLUMN_WIDTH: usize = 5;
+const FIFTY_MOVE_RULE_HALF_MOVES: usize = 100;
+const MAX_RANDOM_GAME_ATTEMPTS: usize = 10;
+const MAX_RANDOM_PLIES_TO_MATE: usize = 300;
+const MAX_RANDOM_PLIES_TO_PIECE: usize = 300;
 
-#[derive(Debug)]
+
+#[derive(Debug, Clone)]
 pub struct Game {
 	board: Board,
+	starting_board: Board,
+	starting_side: Side,
+	starting_half_moves: usize,
+	starting_full_moves: usize,
+	chess960_number: Option<usize>,
 	next_to_act: Side,
 	moves: Vec<Move>,
+	half_moves: usize,
+	full_moves: usize,
+	tags: Vec<(String, String)>,
+	positions: Vec<u64>,
+	undos: Vec<(Undo, usize)>,
 	rng: ThreadRng
 }
 
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GameResult {
+	Win(Side),
+	Draw(DrawReason),
+	InProgress
+}
+
+impl fmt::Display for GameResult {
+
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let result = match self {
+			GameResult::Win(Side::White) => "1-0",
+			GameResult::Win(Side::Black) => "0-1",
+			GameResult::Draw(_) => "1/2-1/2",
+			GameResult::InProgress => "*"
+		};
+		write!(f, "{}", result)
+	}
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DrawReason {
+	Stalemate,
+	FiftyMoves,
+	ThreefoldRepetition,
+	InsufficientMaterial
+}
+
 impl Game {
 
 	pub fn new() -> Self {
 		let board = Board::starting_position();
 		let next_to_act = Side::White;
 		let moves = Vec::new();
+		let positions = vec![board.get_zobrist_key(next_to_act)];
 		Self {
-			board,
+			board: board.clone(),
+			starting_board: board,
+			starting_side: next_to_act,
+			starting_half_moves: 0,
+			starting_full_moves: 1,
+			chess960_number: Some(CHESS960_STANDARD_POSITION),
 			next_to_act,
 			moves,
+			half_moves: 0,
+			full_moves: 1,
+			tags: Vec::new(),
+			positions,
+			undos: Vec::new(),
+			rng: thread_rng()
+		}
+	}
+
+	pub fn from_fen(fen: &str) -> Result<Self, String> {
+		let (board, next_to_act, half_moves, full_moves) = Board::from_fen(fen)?;
+		let positions = vec![board.get_zobrist_key(next_to_act)];
+		let chess960_number = board.get_chess960_number();
+		Ok(Self {
+			board: board.clone(),
+			starting_board: board,
+			starting_side: next_to_act,
+			starting_half_moves: half_moves,
+			starting_full_moves: full_moves,
+			chess960_number,
+			next_to_act,
+			moves: Vec::new(),
+			half_moves,
+			full_moves,
+			tags: Vec::new(),
+			positions,
+			undos: Vec::new(),
 			rng: thread_rng()
+		})
+	}
+
+	pub fn chess960(n: usize) -> Result<Self, String> {
+		let mut to_return = Self::new();
+		to_return.starting_board = Board::chess960_position(n)?;
+		to_return.chess960_number = Some(n);
+		to_return.restart();
+		return Ok(to_return);
+	}
+
+	pub fn from_pgn(pgn: &str) -> Result<Self, String> {
+		match Pgn::parse(pgn)?.first() {
+			None => Err("The PGN doesn't contain any games.".to_string()),
+			Some(p) => p.to_game(),
 		}
 	}
 
+	pub fn all_from_pgn(pgn: &str) -> Result<Vec<Self>, String> {
+		Pgn::parse(pgn)?.iter().map(|p| p.to_game()).collect()
+	}
+
+	pub fn get_tag(&self, name: &str) -> Option<String> {
+		self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+	}
+
+	pub fn set_tag(&mut self, name: &str, value: &str) {
+		match self.tags.iter_mut().find(|(n, _)| n == name) {
+			Some(tag) => tag.1 = value.to_string(),
+			None => self.tags.push((name.to_string(), value.to_string())),
+		};
+	}
+
+	pub fn fen(&self) -> String {
+		self.board.fen(self.next_to_act, self.half_moves, self.full_moves)
+	}
+
+	pub fn to_pgn(&self) -> String {
+		let mut tags: Vec<(String, String)> = vec![
+			("Event", "?"),
+			("Site", "?"),
+			("Date", "????.??.??"),
+			("Round", "?"),
+			("White", "?"),
+			("Black", "?"),
+		].into_iter().map(|(name, default)| (name.to_string(), self.get_tag(name).unwrap_or(default.to_string()))).collect();
+		let result = self.get_result_string();
+		tags.push(("Result".to_string(), result.clone()));
+
+		let is_standard_start = (self.starting_board == Board::starting_position()) && (self.starting_side == Side::White) && (self.starting_full_moves == 1);
+		if !is_standard_start {
+			tags.push(("SetUp".to_string(), "1".to_string()));
+			tags.push(("FEN".to_string(), self.starting_board.fen(self.starting_side, self.starting_half_moves, self.starting_full_moves)));
+		}
+		if self.starting_board.is_chess960() {
+			tags.push(("Variant".to_string(), "Chess960".to_string()));
+		}
+
+		let generated_tags = ["Event", "Site", "Date", "Round", "White", "Black", "Result", "SetUp", "FEN", "Variant"];
+		for (name, value) in &self.tags {
+			if !generated_tags.contains(&name.as_str()) {
+				tags.push((name.clone(), value.clone()));
+			}
+		}
+
+		let mut to_return = "".to_string();
+		for (name, value) in tags {
+			to_return += &format!("[{} \"{}\"]\n", name, value.replace("\\", "\\\\").replace("\"", "\\\""));
+		}
+		to_return += "\n";
+
+		let mut tokens = Vec::new();
+		let mut side = self.starting_side;
+		let mut full_moves = self.starting_full_moves;
+		let mut replay_board = self.starting_board.clone();
+		for (i, m) in self.moves.iter().enumerate() {
+			match side {
+				Side::White => tokens.push(format!("{}.", full_moves)),
+				Side::Black => {
+					if i == 0 {
+						tokens.push(format!("{}...", full_moves));
+					}
+				}
+			};
+			tokens.push(replay_board.get_move_string(*m));
+			replay_board.make_move(*m);
+			if side == Side::Black {
+				full_moves += 1;
+			}
+			side = Side::get_opponent(side);
+		}
+		tokens.push(result);
+
+		let mut line = "".to_string();
+		for token in tokens {
+			if !line.is_empty() && line.len() + 1 + token.len() > PGN_LINE_LENGTH {
+				to_return += &(line + "\n");
+				line = "".to_string();
+			}
+			if !line.is_empty() {
+				line += " ";
+			}
+			line += &token;
+		}
+		to_return += &(line + "\n");
+		return to_return;
+	}
+
+	fn get_result_string(&self) -> String {
+		match self.result() {
+			GameResult::InProgress => self.get_tag("Result").unwrap_or("*".to_string()),
+			result => result.to_string()
+		}
+	}
+
+	pub fn is_starting_position(&self) -> bool {
+		self.moves.is_empty() && (self.starting_side == Side::White) && self.chess960_number.is_some()
+	}
+
 	pub fn get_next_to_act(&self) -> Side {
 		self.next_to_act
 	}
 
+	pub fn get_zobrist_key(&self) -> u64 {
+		self.board.get_zobrist_key(self.next_to_act)
+	}
+
 	pub fn get_board_clone(&self) -> Board {
 		self.board.clone()
 	}
 
 	pub fn clear_board(&mut self) {
 		self.board = Board::empty();
+		self.positions = Vec::new();
+		self.undos = Vec::new();
+		self.starting_board = self.board.clone();
+		self.chess960_number = None;
 	}
 
 	pub fn add_piece(&mut self, side: Side, piece: Piece, square: Square) {
 		self.board.add(side, piece, square);
+		self.positions = Vec::new();
+		self.undos = Vec::new();
+		self.starting_board = self.board.clone();
+		self.chess960_number = None;
 	}
 
 	pub fn get_side_squares(&self, side: Side) -> Vec<Square> {
@@ -47,20 +256,28 @@ impl Game {
 	}
 
 	pub fn pretty_print_moves(&self) -> String {
+		let mut replay_board = self.starting_board.clone();
+		let move_strings: Vec<String> = self.moves.iter().map(|m| {
+			let move_string = replay_board.get_move_string(*m);
+			replay_board.make_move(*m);
+			move_string
+		}).collect();
+		let width = move_strings.iter().map(|m| m.len()).fold(MIN_MOVE_COLUMN_WIDTH, std::cmp::max);
+
 		let mut to_return = "".to_string();
-		let mut side = Side::White;
-		let mut replay_board = Board::starting_position();
-		for m in &self.moves {
-			let move_string = format!("{: >5}", replay_board.get_move_string(*m));
+		let mut side = self.starting_side;
+		if side == Side::Black && !move_strings.is_empty() {
+			to_return += &format!("{: >width$}|", "...", width = width);
+		}
+		for move_string in move_strings {
 			let delimiter = match side {
 				Side::White => "|".to_string(),
 				Side::Black => "\n".to_string()
 			};
-			to_return += &(move_string + &delimiter);
-			replay_board.make_move(*m);
+			to_return += &(format!("{: >width$}", move_string, width = width) + &delimiter);
 			side = Side::get_opponent(side);
 		}
-		return to_return.to_string();
+		return to_return;
 	}
 
 	pub fn pretty_print_board(&self) -> String {
@@ -72,7 +289,41 @@ impl Game {
 	}
 
 	pub fn is_game_over(&self) -> bool {
-		Side::all().into_iter().any(|s| self.is_stalemate_by(s) || self.is_won_by(s))
+		self.result() != GameResult::InProgress
+	}
+
+	pub fn result(&self) -> GameResult {
+		if self.board.is_checkmated(self.next_to_act) {
+			return GameResult::Win(Side::get_opponent(self.next_to_act));
+		}
+		if self.board.is_stalemated(self.next_to_act) {
+			return GameResult::Draw(DrawReason::Stalemate);
+		}
+		if self.is_insufficient_material() {
+			return GameResult::Draw(DrawReason::InsufficientMaterial);
+		}
+		if self.is_draw_by_fifty_moves() {
+			return GameResult::Draw(DrawReason::FiftyMoves);
+		}
+		if self.is_threefold_repetition() {
+			return GameResult::Draw(DrawReason::ThreefoldRepetition);
+		}
+		return GameResult::InProgress;
+	}
+
+	pub fn is_draw_by_fifty_moves(&self) -> bool {
+		self.half_moves >= FIFTY_MOVE_RULE_HALF_MOVES
+	}
+
+	pub fn is_threefold_repetition(&self) -> bool {
+		match self.positions.last() {
+			None => false,
+			Some(current) => self.positions.iter().filter(|p| *p == current).count() >= 3
+		}
+	}
+
+	pub fn is_insufficient_material(&self) -> bool {
+		self.board.is_insufficient_material()
 	}
 
 	pub fn is_stalemate_by(&self, side: Side) -> bool {
@@ -84,9 +335,37 @@ impl Game {
 	}
 
 	pub fn make_move(&mut self, m: Move) {
-		self.board.make_move(m);
+		let half_moves = self.half_moves;
+		let is_pawn_move = self.board.get(m.get_source()).is_some_and(|(_, piece)| piece == Piece::Pawn);
+		if is_pawn_move || self.board.is_capture(m) {
+			self.half_moves = 0;
+		} else {
+			self.half_moves += 1;
+		}
+		if self.next_to_act == Side::Black {
+			self.full_moves += 1;
+		}
+		let undo = self.board.make_move(m);
+		self.undos.push((undo, half_moves));
 		self.moves.push(m);
 		self.next_to_act = Side::get_opponent(self.next_to_act);
+		self.positions.push(self.get_zobrist_key());
+	}
+
+	pub fn undo_move(&mut self) -> Result<Move, String> {
+		let (undo, half_moves) = match self.undos.pop() {
+			None => return Err("There are no moves to undo.".to_string()),
+			Some(u) => u
+		};
+		self.board.unmake_move(undo);
+		self.moves.pop();
+		self.positions.pop();
+		self.half_moves = half_moves;
+		self.next_to_act = Side::get_opponent(self.next_to_act);
+		if self.next_to_act == Side::Black {
+			self.full_moves -= 1;
+		}
+		return Ok(undo.get_move());
 	}
 
 	pub fn make_moves(&mut self, moves: Vec<Move>) {
@@ -117,6 +396,38 @@ impl Game {
 		self.board.get_side_pieces(self.next_to_act, piece)
 	}
 
+	pub fn get_legal_moves(&self, square: Square) -> Vec<Move> {
+		self.board.get_legal_moves(square)
+	}
+
+	pub fn get_discovered_checks(&self) -> Vec<Move> {
+		self.board.get_discovered_checks(self.next_to_act)
+	}
+
+	pub fn get_unmasked_checkers(&self, m: Move) -> Vec<(Square, Piece)> {
+		self.board.get_unmasked_checkers(m)
+	}
+
+	pub fn get_threats(&self) -> Vec<Move> {
+		self.board.get_threats(self.next_to_act)
+	}
+
+	pub fn get_mating_moves(&self) -> Vec<Move> {
+		self.board.get_mating_moves(self.next_to_act)
+	}
+
+	pub fn get_forking_moves(&self) -> Vec<Move> {
+		self.board.get_forking_moves(self.next_to_act)
+	}
+
+	pub fn get_hanging_pieces(&self) -> Vec<Square> {
+		Side::all().into_iter().flat_map(|side| self.board.get_hanging_pieces(side)).collect()
+	}
+
+	pub fn get_pinned_pieces(&self) -> Vec<Square> {
+		Side::all().into_iter().flat_map(|side| self.board.get_pins(side)).map(|pin| pin.get_pinned().0).collect()
+	}
+
 	pub fn get_most_defended_squares(&self, side: Side) -> Vec<(Square, usize)> {
 		let mut to_return: Vec<(Square, usize)> = Square::all().into_iter().map(|s| (s, self.board.get_n_defenders(side, s))).collect();
 		to_return.sort_by(|x, y| y.1.cmp(&x.1));
@@ -129,8 +440,28 @@ impl Game {
 		return to_return;
 	}
 
+	pub fn get_most_xray_defended_squares(&self, side: Side) -> Vec<(Square, usize)> {
+		let mut to_return: Vec<(Square, usize)> = Square::all().into_iter().map(|s| (s, self.board.get_n_xray_defenders(side, s))).collect();
+		to_return.sort_by_key(|x| Reverse(x.1));
+		return to_return;
+	}
+
+	pub fn get_most_xray_attacked_squares(&self, side: Side) -> Vec<(Square, usize)> {
+		let mut to_return: Vec<(Square, usize)> = Square::all().into_iter().map(|s| (s, self.board.get_n_xray_attackers(side, s))).collect();
+		to_return.sort_by_key(|x| Reverse(x.1));
+		return to_return;
+	}
+
 	pub fn parse_moves_from_current_position(&self, s: String) -> Result<Vec<Move>, String> {
-		let move_results: Vec<Result<Move, String>> = Move::parse_move_strings(s).into_iter().map(|m| self.board.try_parse_move(self.next_to_act, &m)).collect();
+		self.parse_moves_for_side(self.next_to_act, s)
+	}
+
+	pub fn parse_opponent_moves_from_current_position(&self, s: String) -> Result<Vec<Move>, String> {
+		self.parse_moves_for_side(Side::get_opponent(self.next_to_act), s)
+	}
+
+	fn parse_moves_for_side(&self, side: Side, s: String) -> Result<Vec<Move>, String> {
+		let move_results: Vec<Result<Move, String>> = Move::parse_move_strings(s).into_iter().map(|m| self.board.try_parse_move(side, &m)).collect();
 		let n_errors = move_results.iter().filter(|m| m.is_err()).count();
 		if n_errors > 0 {
 			let first_error: Option<Result<Move, String>> = move_results.into_iter().filter(|m| m.is_err()).nth(0);
@@ -222,28 +553,105 @@ impl Game {
 		return to_return[0..(to_return.len()) - 2].to_string();
 	}
 
-	pub fn make_random_move(&mut self) {
-		let moves = self.board.get_legal_moves_for_side(self.next_to_act);
-		let result = moves.iter().choose(&mut self.rng);
-		match result {
-			Some(m) => self.make_move(*m),
-			None => panic!("No legal moves remaining!")
-		};
+	pub fn try_make_random_move(&mut self) -> Result<Move, String> {
+		if self.is_game_over() {
+			return Err(format!("No random move can be made because the game is over ({}).", self.result()));
+		}
+		let mut moves: Vec<Move> = self.board.get_legal_moves_for_side(self.next_to_act).into_iter().collect();
+		moves.shuffle(&mut self.rng);
+		for m in moves {
+			self.make_move(m);
+			if !self.is_game_over() {
+				return Ok(m);
+			}
+			self.undo_move()?;
+		}
+		return Err(format!("Every legal move for {} ends the game.", self.next_to_act.to_string()));
 	}
 
-	pub fn make_random_moves(&mut self, n: usize) {
-		for _i in 0..n {
-			self.make_random_move();
+	pub fn try_make_random_moves(&mut self, n: usize) -> Result<(), String> {
+		let before = self.clone();
+		for _attempt in 0..MAX_RANDOM_GAME_ATTEMPTS {
+			if (0..n).all(|_i| self.try_make_random_move().is_ok()) {
+				return Ok(());
+			}
+			*self = before.clone();
 		}
+		return Err(format!("Couldn't make {} random moves without ending the game in {} attempts.", n, MAX_RANDOM_GAME_ATTEMPTS));
 	}
 
-	pub fn make_random_moves_and_end_on_random_side(&mut self, rounds: usize) {
+	pub fn try_make_random_moves_and_end_on_random_side(&mut self, rounds: usize) -> Result<(), String> {
 		let mut n = 2*rounds;
 		let adjust: bool = self.rng.gen();
 		if adjust {
 			n = n - 1;
 		}
-		self.make_random_moves(n);
+		self.try_make_random_moves(n)
+	}
+
+	pub fn try_make_random_moves_until_mate_in(&mut self, n: usize) -> Result<(), String> {
+		let before = self.clone();
+		if self.is_mate_in(n) {
+			return Ok(());
+		}
+		for _attempt in 0..MAX_RANDOM_GAME_ATTEMPTS {
+			for _ply in 0..MAX_RANDOM_PLIES_TO_MATE {
+				if self.try_make_random_move().is_err() {
+					break;
+				}
+				// Checking sequences are much cheaper to search and make for the most recognisable puzzles.
+				if (self.board.get_mate_in_by_checks(self.next_to_act, n) == Some(n)) && self.is_mate_in(n) {
+					return Ok(());
+				}
+			}
+			*self = before.clone();
+		}
+		return Err(format!("Couldn't find a mate in {} within {} random moves in {} attempts.", n, MAX_RANDOM_PLIES_TO_MATE, MAX_RANDOM_GAME_ATTEMPTS));
+	}
+
+	pub fn try_make_random_moves_until_piece(&mut self, rounds: usize, piece: Option<Piece>, square: Option<Square>) -> Result<(Square, Piece), String> {
+		let name = piece.map_or("piece".to_string(), |p| p.to_long_string().to_lowercase());
+		let description = square.map_or(name.clone(), |s| format!("{} on {}", name, s.to_string()));
+		if !self.is_starting_position() {
+			return self.get_random_piece_for_next_to_act(piece, square).ok_or(format!("{} has no {} to move.", self.next_to_act.to_string(), description));
+		}
+		let before = self.clone();
+		for _attempt in 0..MAX_RANDOM_GAME_ATTEMPTS {
+			if self.try_make_random_moves_and_end_on_random_side(rounds).is_ok() {
+				for _ply in 0..MAX_RANDOM_PLIES_TO_PIECE {
+					if let Some(selected) = self.get_random_piece_for_next_to_act(piece, square) {
+						return Ok(selected);
+					}
+					if self.try_make_random_move().is_err() {
+						break;
+					}
+				}
+			}
+			*self = before.clone();
+		}
+		return Err(format!("Couldn't find a {} to move within {} random moves in {} attempts.", description, MAX_RANDOM_PLIES_TO_PIECE, MAX_RANDOM_GAME_ATTEMPTS));
+	}
+
+	fn get_random_piece_for_next_to_act(&mut self, piece: Option<Piece>, square: Option<Square>) -> Option<(Square, Piece)> {
+		let candidates: Vec<(Square, Piece)> = self.board.get_side_squares(self.next_to_act).into_iter()
+			.map(|s| (s, self.board.get(s).unwrap().1))
+			.filter(|(s, p)| square.is_none_or(|x| x == *s) && piece.is_none_or(|x| x == *p))
+			.collect();
+		return candidates.choose(&mut self.rng).copied();
+	}
+
+	pub fn is_mate_in(&self, n: usize) -> bool {
+		self.board.get_mate_in(self.next_to_act, n) == Some(n)
+	}
+
+	pub fn restart(&mut self) {
+		self.board = self.starting_board.clone();
+		self.next_to_act = self.starting_side;
+		self.half_moves = self.starting_half_moves;
+		self.full_moves = self.starting_full_moves;
+		self.moves = Vec::new();
+		self.positions = vec![self.get_zobrist_key()];
+		self.undos = Vec::new();
 	}
 
 }
@@ -252,6 +660,7 @@ impl Game {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::board::Castle;
 
 	#[test]
 	fn game_smoke_test() {
@@ -362,15 +771,224 @@ mod tests {
 			 "  Nh3|   b5\n".to_string() + 
 			&"  Ng1|  Nf6\n".to_string() +
 			&"   g3|  Ne4\n".to_string() +
-			&"   b3|  Nd2\n".to_string() +
+			&"   b3| Nxd2\n".to_string() +
 			&"   h3|   g5\n".to_string() +
 			&"   h4|   c5\n".to_string() +
-			&"  Bd2|   c4\n".to_string() +
+			&" Bxd2|   c4\n".to_string() +
 			&"   c3|   d5\n".to_string() +
 			&"   e3|  Ba6\n".to_string() +
 			&"  Bh3|   h5\n".to_string());
 	}
 
+	#[test]
+	fn game_starts_from_fen() {
+		let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 20").unwrap();
+		assert_eq!(game.get_next_to_act(), Side::Black);
+		assert!(!game.is_starting_position());
+		assert_eq!(game.fen(), "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 20");
+
+		game.make_moves_from_string("Kf8, Kd1, Rxa1".to_string());
+		assert_eq!(game.fen(), "5k1r/8/8/8/8/8/8/r2K3R w - - 0 22");
+		assert_eq!(game.pretty_print_moves(), "  ...|  Kf8\n  Kd1|Rxa1+\n".to_string());
+
+		let mut game = Game::from_fen("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+		game.make_moves_from_string("exd8=Q, Kf7, Qd1".to_string());
+		assert_eq!(game.pretty_print_moves(), "exd8=Q+|    Kf7\n    Qd1|".to_string());
+
+		assert!(Game::from_fen("not a fen").is_err());
+		assert!(Game::new().is_starting_position());
+	}
+
+	#[test]
+	fn game_exports_pgn() {
+		let mut game = Game::new();
+		game.set_tag("White", "Carlsen, \"Magnus\"");
+		game.set_tag("Annotator", "Coach");
+		game.make_moves_from_string("e4, e5, Qh5, Nc6, Bc4, Nf6, Qxf7".to_string());
+		assert_eq!(
+			game.to_pgn(),
+			"[Event \"?\"]\n".to_string() +
+			"[Site \"?\"]\n" +
+			"[Date \"????.??.??\"]\n" +
+			"[Round \"?\"]\n" +
+			"[White \"Carlsen, \\\"Magnus\\\"\"]\n" +
+			"[Black \"?\"]\n" +
+			"[Result \"1-0\"]\n" +
+			"[Annotator \"Coach\"]\n" +
+			"\n" +
+			"1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"
+		);
+
+		let imported = Game::from_pgn(&game.to_pgn()).unwrap();
+		assert_eq!(imported.get_board_clone(), game.get_board_clone());
+		assert_eq!(imported.get_tag("White"), Some("Carlsen, \"Magnus\"".to_string()));
+
+		let mut game = Game::from_fen("4k3/8/8/8/8/8/q7/4K3 b - - 0 30").unwrap();
+		game.make_moves_from_string("Qa3, Kf2".to_string());
+		let pgn = game.to_pgn();
+		assert!(pgn.contains("[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/q7/4K3 b - - 0 30\"]\n"));
+		assert!(pgn.ends_with("\n30... Qa3 31. Kf2 *\n"));
+		assert_eq!(Game::from_pgn(&pgn).unwrap().get_board_clone(), game.get_board_clone());
+	}
+
+	#[test]
+	fn game_detects_draws() {
+		let mut game = Game::new();
+		game.make_moves_from_string("Nf3, Nf6, Ng1, Ng8, Nf3, Nf6, Ng1".to_string());
+		assert!(!game.is_threefold_repetition());
+		assert_eq!(game.result(), GameResult::InProgress);
+		game.make_moves_from_string("Ng8".to_string());
+		assert!(game.is_threefold_repetition());
+		assert!(game.is_game_over());
+		assert_eq!(game.result(), GameResult::Draw(DrawReason::ThreefoldRepetition));
+		assert!(game.to_pgn().ends_with("1/2-1/2\n"));
+
+		let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 98 70").unwrap();
+		game.make_moves_from_string("Ra2".to_string());
+		assert!(!game.is_draw_by_fifty_moves());
+		game.make_moves_from_string("Kd7".to_string());
+		assert!(game.is_draw_by_fifty_moves());
+		assert_eq!(game.result(), GameResult::Draw(DrawReason::FiftyMoves));
+
+		let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 70").unwrap();
+		game.make_moves_from_string("e4".to_string());
+		assert!(!game.is_draw_by_fifty_moves());
+
+		let mut game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+		assert!(!game.is_insufficient_material());
+		game.make_moves_from_string("Kxd2".to_string());
+		assert!(game.is_insufficient_material());
+		assert_eq!(game.result(), GameResult::Draw(DrawReason::InsufficientMaterial));
+		assert_eq!(game.result().to_string(), "1/2-1/2");
+
+		let game = Game::from_fen("k7/2Q5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+		assert_eq!(game.result(), GameResult::InProgress);
+		let game = Game::from_fen("k7/2Q5/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+		assert_eq!(game.result(), GameResult::Draw(DrawReason::Stalemate));
+	}
+
+	#[test]
+	fn game_makes_random_moves_without_ending() {
+		let mut game = Game::new();
+		game.try_make_random_moves_and_end_on_random_side(20).unwrap();
+		assert!(!game.is_game_over());
+		assert!(game.get_moves().len() >= 39);
+
+		let mut game = Game::from_fen("7k/8/6K1/8/8/8/8/3Q4 w - - 0 1").unwrap();
+		let m = game.try_make_random_move().unwrap();
+		assert!(!game.is_game_over());
+		assert!(game.get_moves() == vec![m]);
+
+		let mut game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+		assert!(game.is_game_over());
+		assert!(game.try_make_random_move().is_err());
+		assert!(game.try_make_random_moves(3).is_err());
+		assert_eq!(game.get_moves().len(), 0);
+
+		let mut game = Game::from_fen("k7/2Q5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+		assert!(game.to_pgn().ends_with("*\n"));
+		let m = game.try_make_random_move().unwrap();
+		assert!(game.get_moves() == vec![m]);
+
+		let mut game = Game::new();
+		game.try_make_random_moves(6).unwrap();
+		game.restart();
+		assert!(game.is_starting_position());
+	}
+
+	#[test]
+	fn game_makes_random_moves_until_piece() {
+		let mut game = Game::new();
+		let (square, piece) = game.try_make_random_moves_until_piece(2, Some(Piece::Knight), None).unwrap();
+		assert_eq!(piece, Piece::Knight);
+		assert_eq!(game.get_board_clone().get(square), Some((game.get_next_to_act(), Piece::Knight)));
+
+		let mut game = Game::from_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1").unwrap();
+		assert_eq!(game.try_make_random_moves_until_piece(2, None, Some(Square::from_string("e4"))), Ok((Square::from_string("e4"), Piece::Knight)));
+		assert!(game.try_make_random_moves_until_piece(2, Some(Piece::Queen), None).is_err());
+		assert!(game.try_make_random_moves_until_piece(2, None, Some(Square::from_string("e8"))).is_err());
+		assert_eq!(game.get_moves().len(), 0);
+
+		let mut game = Game::new();
+		game.add_piece(Side::White, Piece::Queen, Square::from_string("e4"));
+		assert!(!game.is_starting_position());
+		game.clear_board();
+		assert!(!game.is_starting_position());
+		game.add_piece(Side::White, Piece::Knight, Square::from_string("e4"));
+		assert_eq!(game.try_make_random_moves_until_piece(2, None, None), Ok((Square::from_string("e4"), Piece::Knight)));
+	}
+
+	#[test]
+	fn game_makes_random_moves_until_mate() {
+		let mut game = Game::new();
+		game.try_make_random_moves_until_mate_in(1).unwrap();
+		assert!(!game.get_mating_moves().is_empty());
+		assert!(!game.is_game_over());
+
+		let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+		game.try_make_random_moves_until_mate_in(1).unwrap();
+		assert_eq!(game.get_moves().len(), 0);
+
+		let mut game = Game::new();
+		game.try_make_random_moves_until_mate_in(2).unwrap();
+		assert!(game.is_mate_in(2));
+		assert!(game.get_mating_moves().is_empty());
+	}
+
+	#[test]
+	fn game_undoes_moves() {
+		let mut game = Game::new();
+		assert!(game.undo_move().is_err());
+		let fens: Vec<String> = vec![game.fen()];
+		let fens = "e4, d5, e5, f5, exf6, Nxf6, Nf3, Bg4, Be2, Nc6, O-O, Qd6".split(", ").fold(fens, |mut accumulator, m| {
+			game.make_moves_from_string(m.to_string());
+			accumulator.push(game.fen());
+			accumulator
+		});
+		assert!(!game.get_board_clone().get_castling_rights(Side::White, Castle::Kingside));
+
+		for i in (0..(fens.len() - 1)).rev() {
+			assert!(game.undo_move().is_ok());
+			assert_eq!(game.fen(), fens[i]);
+			assert_eq!(game.get_moves().len(), i);
+		}
+		assert!(game.is_starting_position());
+		assert_eq!(game.get_board_clone(), Board::starting_position());
+		assert_eq!(game.get_zobrist_key(), Game::new().get_zobrist_key());
+		assert!(game.undo_move().is_err());
+
+		game.make_moves_from_string("Nf3, Nf6, Ng1, Ng8, Nf3, Nf6, Ng1, Ng8".to_string());
+		assert!(game.is_threefold_repetition());
+		assert_eq!(game.undo_move(), Ok(Move::new(Square::from_string("f6"), Square::from_string("g8"))));
+		assert!(!game.is_threefold_repetition());
+	}
+
+	#[test]
+	fn game_parses_san_and_uci_moves() {
+		let mut game = Game::new();
+		game.make_moves_from_string("e2e4, e5, g1f3, Nc6".to_string());
+		let moves = game.parse_moves_from_current_position("f1b5, Bc4, d2d4".to_string()).unwrap();
+		assert_eq!(moves, game.parse_moves_from_current_position("Bb5, f1c4, d4".to_string()).unwrap());
+		assert!(game.parse_moves_from_current_position("Bb5, f1f3".to_string()).is_err());
+		assert_eq!(game.parse_sequential_moves("f1c4, g8f6, Ng5".to_string()).unwrap().len(), 3);
+	}
+
+	#[test]
+	fn game_starts_from_chess960_positions() {
+		assert!(Game::chess960(960).is_err());
+		let mut game = Game::chess960(0).unwrap();
+		assert!(game.is_starting_position());
+		game.make_moves_from_string("e4, e5, Nf3".to_string());
+		assert!(!game.is_starting_position());
+
+		let pgn = game.to_pgn();
+		assert!(pgn.contains("[FEN \"bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1\"]\n[Variant \"Chess960\"]\n"));
+		assert_eq!(Game::from_pgn(&pgn).unwrap().get_board_clone(), game.get_board_clone());
+		assert!(!Game::new().to_pgn().contains("Variant"));
+		assert!(Game::from_fen("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1").unwrap().is_starting_position());
+		assert!(!Game::from_fen("bbqnnrkr/pppppppp/8/8/4P3/8/PPPP1PPP/BBQNNRKR b KQkq - 0 1").unwrap().is_starting_position());
+	}
+
 	#[test]
 	fn game_gets_most_defended_squares() {
 		let game = Game::new();
@@ -8,14 +8,18 @@ mod board;
 mod trainer;
 mod color;
 mod evaluation;
+mod pgn;
 
-use clap::{App, SubCommand, Arg};
+use clap::{App, ArgMatches, SubCommand, Arg};
 use evaluation::Evaluator;
 use text_io::read;
 use std::collections::{HashSet};
-use crate::trainer::{Trainer, TrainerMode, Target};
+use crate::trainer::{Trainer, TrainerBuilder, TrainerMode, Target};
 use crate::game::{Game};
-use crate::board::{Board, Side, Move, Piece};
+use crate::board::{Board, Side, Piece, Square, CHESS960_POSITIONS};
+use rand::{Rng, thread_rng};
+
+const MAX_PERFT_DEPTH: usize = 6;
 
 
 fn main() {
@@ -25,85 +29,110 @@ fn main() {
 		.author("Chris Dugan <c.m.s.dugan@gmail.com>")
 		.about("Chess Vision Tester")
 		.subcommand(
-			SubCommand::with_name("checks")
-				.about("Can you find all the checks in a position?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				)
+			with_position_args(
+				SubCommand::with_name("checks")
+					.about("Can you find all the checks in a position?")
+			)
 		).subcommand(
-			SubCommand::with_name("captures")
-				.about("Can you find all of the captures in a position?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				)
+			with_position_args(
+				SubCommand::with_name("captures")
+					.about("Can you find all of the captures in a position?")
+			)
 		).subcommand(
-			SubCommand::with_name("sequential")
-				.about("Can you find all of the checks as we walk through random moves?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				)
+			with_position_args(
+				SubCommand::with_name("sequential")
+					.about("Can you find all of the checks as we walk through random moves?")
+			)
 		).subcommand(
-			SubCommand::with_name("position")
-				.about("Can you identify the positions of all of the pieces?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				)
+			with_position_args(
+				SubCommand::with_name("position")
+					.about("Can you identify the positions of all of the pieces?")
+			)
 		).subcommand(
-			SubCommand::with_name("defended")
-				.about("Can you identify the 3 most defended pieces or squares?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				).arg(
-					Arg::with_name("squares")
-						.short("s")
-						.long("squares")
-				)
+			with_position_args(
+				SubCommand::with_name("pins")
+					.about("Can you find all of the pinned pieces in a position?")
+			)
 		).subcommand(
-			SubCommand::with_name("attacked")
-				.about("Can you identify the 3 most attacked pieces or squares?")
-				.arg(
-					Arg::with_name("blindfold")
-						.short("b")
-						.long("blindfold")
-				).arg(
-					Arg::with_name("whites_perspective_only")
-						.short("w")
-						.long("whites-perspective-only")
-				).arg(
-					Arg::with_name("squares")
-						.short("s")
-						.long("squares")
-				)
+			with_position_args(
+				SubCommand::with_name("forks")
+					.about("Can you find all of the forks in a position?")
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("mates")
+					.about("Can you find all of the checkmates in a position?")
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("mate-in")
+					.about("Can you find a forced mate in N moves?")
+					.arg(
+						Arg::with_name("depth")
+							.short("d")
+							.long("depth")
+							.takes_value(true)
+					)
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("threats")
+					.about("Can you find everything your opponent is threatening?")
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("discovered")
+					.about("Can you find all of the discovered checks in a position?")
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("hanging")
+					.about("Can you find all of the pieces that can be won in a position?")
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("defended")
+					.about("Can you identify the 3 most defended pieces or squares?")
+					.arg(
+						Arg::with_name("squares")
+							.short("s")
+							.long("squares")
+					).arg(
+						Arg::with_name("xray")
+							.short("x")
+							.long("xray")
+					)
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("attacked")
+					.about("Can you identify the 3 most attacked pieces or squares?")
+					.arg(
+						Arg::with_name("squares")
+							.short("s")
+							.long("squares")
+					).arg(
+						Arg::with_name("xray")
+							.short("x")
+							.long("xray")
+					)
+			)
+		).subcommand(
+			with_position_args(
+				SubCommand::with_name("moves")
+					.about("Can you find all of the legal moves for a random piece?")
+					.arg(
+						Arg::with_name("piece")
+							.short("p")
+							.long("piece")
+							.takes_value(true)
+					).arg(
+						Arg::with_name("square")
+							.short("s")
+							.long("square")
+							.takes_value(true)
+					)
+			)
 		).subcommand(
 			SubCommand::with_name("path")
 				.about("Can you give the shortest path for a random piece between two random squares.")
@@ -132,77 +161,97 @@ fn main() {
 						.short("f")
 						.long("fen")
 						.takes_value(true)
+					).arg(
+						Arg::with_name("pgn")
+						.long("pgn")
+						.takes_value(true)
 					)
+		).subcommand(
+			SubCommand::with_name("perft")
+				.about("Count the leaf nodes of the legal move tree to a given depth")
+				.arg(
+					Arg::with_name("fen")
+						.short("f")
+						.long("fen")
+						.takes_value(true)
+				).arg(
+					Arg::with_name("chess960")
+						.long("chess960")
+						.takes_value(true)
+						.min_values(0)
+				).arg(
+					Arg::with_name("depth")
+						.short("d")
+						.long("depth")
+						.takes_value(true)
+				)
 		).get_matches();
 
-	if let Some(_matches) = matches.subcommand_matches("checks") {
-		let mut builder = Trainer::builder(TrainerMode::Checks);
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
-		}
-		let mut trainer = builder.build();
-		trainer.run();
+	if let Some(matches) = matches.subcommand_matches("checks") {
+		run_trainer(Trainer::builder(TrainerMode::Checks), matches);
 	} else if let Some(matches) = matches.subcommand_matches("captures") {
-		let mut builder = Trainer::builder(TrainerMode::Captures);
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
-		}
-		let mut trainer = builder.build();
-		trainer.run();
+		run_trainer(Trainer::builder(TrainerMode::Captures), matches);
 	} else if let Some(matches) = matches.subcommand_matches("sequential") {
-		let mut builder = Trainer::builder(TrainerMode::Sequential);
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
-		}
-		let mut trainer = builder.build();
-		trainer.run();
+		run_trainer(Trainer::builder(TrainerMode::Sequential), matches);
 	} else if let Some(matches) = matches.subcommand_matches("position") {
-		let mut builder = Trainer::builder(TrainerMode::Position);
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
-		}
-		let mut trainer = builder.build();
-		trainer.run();
+		run_trainer(Trainer::builder(TrainerMode::Position), matches);
+	} else if let Some(matches) = matches.subcommand_matches("pins") {
+		run_trainer(Trainer::builder(TrainerMode::Pins), matches);
+	} else if let Some(matches) = matches.subcommand_matches("forks") {
+		run_trainer(Trainer::builder(TrainerMode::Forks), matches);
+	} else if let Some(matches) = matches.subcommand_matches("mates") {
+		run_trainer(Trainer::builder(TrainerMode::Mates), matches);
+	} else if let Some(matches) = matches.subcommand_matches("mate-in") {
+		let depth = match matches.value_of("depth").unwrap_or("2").parse::<usize>() {
+			Ok(d) if d > 0 => d,
+			_ => {
+				println!("The depth must be a positive number of moves.");
+				return;
+			}
+		};
+		run_trainer(Trainer::builder(TrainerMode::MateIn(depth)), matches);
+	} else if let Some(matches) = matches.subcommand_matches("threats") {
+		run_trainer(Trainer::builder(TrainerMode::Threats), matches);
+	} else if let Some(matches) = matches.subcommand_matches("discovered") {
+		run_trainer(Trainer::builder(TrainerMode::Discovered), matches);
+	} else if let Some(matches) = matches.subcommand_matches("hanging") {
+		run_trainer(Trainer::builder(TrainerMode::Hanging), matches);
 	} else if let Some(matches) = matches.subcommand_matches("defended") {
 		let target = match matches.is_present("squares") {
 			true => Target::Square,
 			false => Target::Piece,
 		};
 		let mut builder = Trainer::builder(TrainerMode::MostDefended(target));
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
+		if matches.is_present("xray") {
+			builder = builder.xray();
 		}
-		let mut trainer = builder.build();
-		trainer.run();
+		run_trainer(builder, matches);
 	} else if let Some(matches) = matches.subcommand_matches("attacked") {
 		let target = match matches.is_present("squares") {
 			true => Target::Square,
 			false => Target::Piece,
 		};
 		let mut builder = Trainer::builder(TrainerMode::MostAttacked(target));
-		if matches.is_present("blindfold") {
-			builder = builder.blindfold();
-		}
-		if matches.is_present("whites-perspective-only") {
-			builder = builder.whites_perspective_only();
+		if matches.is_present("xray") {
+			builder = builder.xray();
 		}
-		let mut trainer = builder.build();
-		trainer.run();
+		run_trainer(builder, matches);
+	} else if let Some(matches) = matches.subcommand_matches("moves") {
+		let piece = match matches.value_of("piece").map(|p| Piece::try_parse(p.to_string())).transpose() {
+			Ok(p) => p,
+			Err(e) => {
+				println!("{}", e);
+				return;
+			}
+		};
+		let square = match matches.value_of("square").map(Square::try_parse).transpose() {
+			Ok(s) => s,
+			Err(e) => {
+				println!("{}", e);
+				return;
+			}
+		};
+		run_trainer(Trainer::builder(TrainerMode::Moves(piece, square)), matches);
 	} else if let Some(matches) = matches.subcommand_matches("path") {
 		let piece = match matches.value_of("piece") {
 			None => Piece::get_random_non_pawn(),
@@ -222,21 +271,146 @@ fn main() {
 		let mut trainer = builder.build();
 		trainer.run();
 	} else if let Some(matches) = matches.subcommand_matches("evaluate") {
-		match matches.value_of("fen") {
-			None => println!("No FEN provided!"),
-			Some(fen) => {
+		match (matches.value_of("fen"), matches.value_of("pgn")) {
+			(Some(fen), _) => {
 				match Evaluator::evaluate_from_fen(fen.to_string()) {
 					Ok(eval) => println!("Evaluation: {:?}", eval),
 					Err(s) => println!("{}", s),
 				}
+			},
+			(None, Some(path)) => {
+				match read_file(path).and_then(|pgn| Game::from_pgn(&pgn)).and_then(|game| Evaluator::evaluate_from_fen(game.fen())) {
+					Ok(eval) => println!("Evaluation: {:?}", eval),
+					Err(s) => println!("{}", s),
+				}
+			},
+			(None, None) => println!("No FEN or PGN provided!"),
+		}
+	} else if let Some(matches) = matches.subcommand_matches("perft") {
+		let position = match (matches.value_of("fen"), matches.is_present("chess960")) {
+			(Some(fen), _) => Board::from_fen(fen).map(|(board, side, _, _)| (board, side)),
+			(None, true) => parse_chess960(matches.value_of("chess960")).and_then(Board::chess960_position).map(|board| (board, Side::White)),
+			(None, false) => Ok((Board::starting_position(), Side::White)),
+		};
+		let (board, side) = match position {
+			Ok(p) => p,
+			Err(e) => {
+				println!("{}", e);
+				return;
+			}
+		};
+		let depth = match matches.value_of("depth").unwrap_or("3").parse::<usize>() {
+			Ok(d) if d <= MAX_PERFT_DEPTH => d,
+			Ok(d) => {
+				println!("Invalid depth: {} is deeper than the maximum of {}.", d, MAX_PERFT_DEPTH);
+				return;
+			},
+			Err(e) => {
+				println!("Invalid depth: {}", e);
+				return;
 			}
+		};
+		let mut divide: Vec<(String, usize)> = board.divide(side, depth).into_iter().map(|(m, n)| (m.to_uci(), n)).collect();
+		divide.sort();
+		for (m, n) in &divide {
+			println!("{}: {}", m, n);
 		}
+		let nodes: usize = match depth {
+			0 => 1,
+			_ => divide.iter().map(|(_, n)| n).sum()
+		};
+		println!("\nNodes searched: {}", nodes);
 	} else {
 		panic!("Invalid subcommand");
 	}
 
 }
 
+fn with_position_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+	app.arg(
+		Arg::with_name("blindfold")
+			.short("b")
+			.long("blindfold")
+	).arg(
+		Arg::with_name("whites_perspective_only")
+			.short("w")
+			.long("whites-perspective-only")
+	).arg(
+		Arg::with_name("fen")
+			.short("f")
+			.long("fen")
+			.takes_value(true)
+	).arg(
+		Arg::with_name("pgn")
+			.long("pgn")
+			.takes_value(true)
+	).arg(
+		Arg::with_name("export_pgn")
+			.long("export-pgn")
+			.takes_value(true)
+	).arg(
+		Arg::with_name("chess960")
+			.long("chess960")
+			.takes_value(true)
+			.min_values(0)
+	)
+}
+
+fn configure_builder(builder: TrainerBuilder, matches: &ArgMatches) -> Result<TrainerBuilder, String> {
+	let mut builder = builder;
+	if matches.is_present("blindfold") {
+		builder = builder.blindfold();
+	}
+	if matches.is_present("whites_perspective_only") {
+		builder = builder.whites_perspective_only();
+	}
+	if let Some(fen) = matches.value_of("fen") {
+		builder = builder.with_fen(fen)?;
+	}
+	if let Some(path) = matches.value_of("pgn") {
+		builder = builder.with_pgn(&read_file(path)?)?;
+	}
+	if matches.is_present("chess960") {
+		builder = builder.with_chess960(parse_chess960(matches.value_of("chess960"))?)?;
+	}
+	return Ok(builder);
+}
+
+fn run_trainer(builder: TrainerBuilder, matches: &ArgMatches) {
+	let mut trainer = match configure_builder(builder, matches) {
+		Ok(b) => b.build(),
+		Err(e) => {
+			println!("{}", e);
+			return;
+		}
+	};
+	trainer.run();
+	export_pgn(&trainer, matches);
+}
+
+fn export_pgn(trainer: &Trainer, matches: &ArgMatches) {
+	if let Some(path) = matches.value_of("export_pgn") {
+		if let Err(e) = write_file(path, &trainer.to_pgn()) {
+			println!("{}", e);
+		}
+	}
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+	std::fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))
+}
+
+fn write_file(path: &str, contents: &str) -> Result<(), String> {
+	std::fs::write(path, contents).map_err(|e| format!("Couldn't write {}: {}", path, e))
+}
+
+fn parse_chess960(n: Option<&str>) -> Result<usize, String> {
+	match n {
+		None => Ok(thread_rng().gen_range(0, CHESS960_POSITIONS)),
+		Some(n) => n.parse::<usize>().map_err(|e| format!("Invalid Chess960 position number {:?}: {}", n, e)),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -0,0 +1,265 @@
+use crate::game::{Game};
+
+static RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+static EN_PASSANT_SUFFIX: &str = "e.p.";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pgn {
+	tags: Vec<(String, String)>,
+	moves: Vec<String>,
+	result: Option<String>,
+}
+
+impl Pgn {
+
+	pub fn new() -> Self {
+		Self {
+			tags: Vec::new(),
+			moves: Vec::new(),
+			result: None,
+		}
+	}
+
+	pub fn parse(s: &str) -> Result<Vec<Self>, String> {
+		let tokens = PgnToken::tokenize(s)?;
+		let mut to_return = Vec::new();
+		let mut current = Self::new();
+		for token in tokens {
+			match token {
+				PgnToken::Tag(name, value) => {
+					if !current.moves.is_empty() {
+						to_return.push(current);
+						current = Self::new();
+					}
+					current.tags.push((name, value));
+				},
+				PgnToken::Move(m) => {
+					current.moves.push(m);
+				},
+				PgnToken::Result(r) => {
+					current.result = Some(r);
+					to_return.push(current);
+					current = Self::new();
+				}
+			}
+		}
+		if !current.tags.is_empty() || !current.moves.is_empty() {
+			to_return.push(current);
+		}
+		return Ok(to_return);
+	}
+
+	pub fn get_tag(&self, name: &str) -> Option<String> {
+		self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+	}
+
+	pub fn to_game(&self) -> Result<Game, String> {
+		let mut game = match self.get_tag("FEN") {
+			None => Game::new(),
+			Some(fen) => Game::from_fen(&fen)?,
+		};
+		for (name, value) in &self.tags {
+			game.set_tag(name, value);
+		}
+
+		for (i, move_string) in self.moves.iter().enumerate() {
+			let side = game.get_next_to_act();
+			let board = game.get_board_clone();
+			let m = match board.try_parse_move(side, move_string) {
+				Ok(m) => m,
+				Err(e) => return Err(format!("Couldn't parse {} (ply {}) for {}: {}", move_string, i + 1, side.to_string(), e)),
+			};
+			if !board.is_legal_move(m) {
+				return Err(format!("{} (ply {}) is not a legal move for {}.", move_string, i + 1, side.to_string()));
+			}
+			game.make_move(m);
+		}
+		return Ok(game);
+	}
+
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum PgnToken {
+	Tag(String, String),
+	Move(String),
+	Result(String),
+}
+
+impl PgnToken {
+
+	fn tokenize(s: &str) -> Result<Vec<Self>, String> {
+		let chars: Vec<char> = s.chars().collect();
+		let mut to_return = Vec::new();
+		let mut i = 0;
+		let mut is_line_start = true;
+		while i < chars.len() {
+			let c = chars[i];
+			if c == '%' && is_line_start {
+				i = Self::skip_past(&chars, i, '\n');
+			} else if c == '[' {
+				let end = Self::find(&chars, i, ']').ok_or(format!("Unterminated tag starting at character {}.", i))?;
+				to_return.push(Self::parse_tag(&chars[(i + 1)..end].iter().collect::<String>())?);
+				i = end + 1;
+			} else if c == '{' {
+				let end = Self::find(&chars, i, '}').ok_or(format!("Unterminated comment starting at character {}.", i))?;
+				i = end + 1;
+			} else if c == ';' {
+				i = Self::skip_past(&chars, i, '\n');
+			} else if c == '(' {
+				i = Self::skip_variation(&chars, i)?;
+			} else if c == ')' {
+				return Err(format!("Unmatched ')' at character {}.", i));
+			} else if c.is_whitespace() {
+				i += 1;
+			} else {
+				let start = i;
+				while i < chars.len() && !chars[i].is_whitespace() && !"[]{}();".contains(chars[i]) {
+					i += 1;
+				}
+				let word: String = chars[start..i].iter().collect();
+				to_return.append(&mut Self::parse_word(&word));
+			}
+			is_line_start = c == '\n';
+		}
+		return Ok(to_return);
+	}
+
+	fn parse_tag(s: &str) -> Result<Self, String> {
+		let s = s.trim();
+		let name: String = s.chars().take_while(|c| !c.is_whitespace()).collect();
+		let rest = s[name.len()..].trim();
+		if name.is_empty() || rest.len() < 2 || !rest.starts_with('"') || !rest.ends_with('"') {
+			return Err(format!("Invalid tag pair: [{}]", s));
+		}
+		let value = rest[1..(rest.len() - 1)].replace("\\\"", "\"").replace("\\\\", "\\");
+		return Ok(PgnToken::Tag(name, value));
+	}
+
+	fn parse_word(word: &str) -> Vec<Self> {
+		if RESULTS.contains(&word) {
+			return vec![PgnToken::Result(word.to_string())];
+		}
+		if word.starts_with('$') || word == EN_PASSANT_SUFFIX {
+			return Vec::new();
+		}
+
+		// Move numbers may be attached to the move, e.g. "12.e4" or "12...e5".
+		let without_number = word.trim_start_matches(|c: char| c.is_ascii_digit());
+		let without_number = if without_number.len() < word.len() && without_number.starts_with('.') {
+			without_number.trim_start_matches('.')
+		} else {
+			word
+		};
+		let without_number = without_number.strip_suffix(EN_PASSANT_SUFFIX).unwrap_or(without_number);
+		if without_number.is_empty() {
+			return Vec::new();
+		}
+		return vec![PgnToken::Move(without_number.to_string())];
+	}
+
+	fn find(chars: &[char], start: usize, target: char) -> Option<usize> {
+		(start..chars.len()).find(|i| chars[*i] == target)
+	}
+
+	fn skip_past(chars: &[char], start: usize, target: char) -> usize {
+		Self::find(chars, start, target).map_or(chars.len(), |i| i + 1)
+	}
+
+	fn skip_variation(chars: &[char], start: usize) -> Result<usize, String> {
+		let mut depth = 0;
+		let mut i = start;
+		while i < chars.len() {
+			match chars[i] {
+				'(' => depth += 1,
+				')' => {
+					depth -= 1;
+					if depth == 0 {
+						return Ok(i + 1);
+					}
+				},
+				'{' => {
+					i = Self::find(chars, i, '}').ok_or(format!("Unterminated comment starting at character {}.", i))?;
+				},
+				';' => {
+					i = Self::skip_past(chars, i, '\n') - 1;
+				},
+				_ => {}
+			};
+			i += 1;
+		}
+		return Err(format!("Unterminated variation starting at character {}.", start));
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::board::{Board, Piece, Side, Square};
+
+	#[test]
+	fn pgn_parses_a_game() {
+		let pgn = r#"
+[Event "Club Championship"]
+[Site "London"]
+[Date "2020.01.01"]
+[Round "1"]
+[White "Player, A"]
+[Black "Player, B"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {the most common move} Nc6 3. Bc4 (3. Bb5 a6 (3... Nf6) 4. Ba4) 3... Nf6?!
+4. Ng5 $6 d5 5. exd5 Nxd5?? 6. Nxf7! Kxf7 7. Qf3+ Ke6 8. Nc3 Ncb4 9. O-O c6 ; a rest of line comment
+10. d4 1-0
+"#;
+		let pgns = Pgn::parse(pgn).unwrap();
+		assert_eq!(pgns.len(), 1);
+		let pgn = &pgns[0];
+		assert_eq!(pgn.get_tag("White"), Some("Player, A".to_string()));
+		assert_eq!(pgn.result, Some("1-0".to_string()));
+		assert_eq!(pgn.moves.len(), 19);
+
+		let game = pgn.to_game().unwrap();
+		assert_eq!(game.get_moves().len(), 19);
+		assert_eq!(game.get_next_to_act(), Side::Black);
+		assert_eq!(game.get_tag("Event"), Some("Club Championship".to_string()));
+		let board = game.get_board_clone();
+		assert_eq!(board.get(Square::from_string("g1")), Some((Side::White, Piece::King)));
+		assert_eq!(board.get(Square::from_string("e6")), Some((Side::Black, Piece::King)));
+	}
+
+	#[test]
+	fn pgn_parses_multiple_games() {
+		let pgn = "[Event \"A\"]\n\n1.d4 d5 2.c4 dxc4 *\n\n[Event \"B\"]\n[SetUp \"1\"]\n[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n\n1. a8=Q+ Kd7 1/2-1/2\n";
+		let games = Game::all_from_pgn(pgn).unwrap();
+		assert_eq!(games.len(), 2);
+		assert_eq!(games[0].get_moves().len(), 4);
+		assert_eq!(games[1].get_moves().len(), 2);
+		assert_eq!(games[1].get_board_clone().get(Square::from_string("a8")), Some((Side::White, Piece::Queen)));
+
+		let game = Game::from_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0").unwrap();
+		assert!(game.is_won_by(Side::White));
+		assert!(game.get_board_clone() != Board::starting_position());
+	}
+
+	#[test]
+	fn pgn_skips_en_passant_suffixes() {
+		for pgn in ["1. e4 a6 2. e5 d5 3. exd6 e.p. *", "1. e4 a6 2. e5 d5 3. exd6e.p. *"] {
+			let game = Game::from_pgn(pgn).unwrap();
+			assert_eq!(game.get_moves().len(), 5);
+			let board = game.get_board_clone();
+			assert_eq!(board.get(Square::from_string("d6")), Some((Side::White, Piece::Pawn)));
+			assert_eq!(board.get(Square::from_string("d5")), None);
+		}
+	}
+
+	#[test]
+	fn pgn_reports_errors() {
+		assert!(Game::from_pgn("1. e4 e5 2. Ke3").is_err());
+		assert!(Game::from_pgn("1. e4 {unterminated").is_err());
+		assert!(Game::from_pgn("1. e4 (1. d4").is_err());
+		assert!(Game::from_pgn("[Event \"A\" 1. e4").is_err());
+		assert!(Game::from_pgn("").is_err());
+	}
+}
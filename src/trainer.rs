@@ -6,6 +6,7 @@ use text_io::read;
 use crate::board::{Board, Move, Square, File, Rank, Side, Path, Piece, SquareColor};
 use crate::game::{Game};
 use crate::color::Color;
+use rand::{seq::IteratorRandom, thread_rng};
 
 const DEFAULT_N_ROUNDS: usize = 20;
 const N_ROUNDS_BEFORE_SEQUENTIAL: usize = 3;
@@ -30,6 +31,7 @@ impl Trainer {
 			output: TrainerOutput::StdOut,
 			blindfold: false,
 			whites_perspective_only: false,
+			xray: false,
 			game: Game::new()
 		}
 	}
@@ -42,9 +44,16 @@ impl Trainer {
 					self.state = TrainerState::Running;
 				},
 				TrainerState::Running => {
-					self.transform();
-					self.prompt();
-					self.state = TrainerState::WaitingForInput;
+					match self.transform() {
+						Ok(_) => {
+							self.prompt();
+							self.state = TrainerState::WaitingForInput;
+						},
+						Err(e) => {
+							self.emit(Trainer::get_error(e).unwrap_err());
+							self.state = TrainerState::Finished;
+						}
+					};
 				},
 				TrainerState::WaitingForInput => {
 					let input = self.get_input();
@@ -79,18 +88,34 @@ impl Trainer {
 		}
 	}
 
+	pub fn to_pgn(&self) -> String {
+		self.game.to_pgn()
+	}
+
 	fn out_of_prompts(&self) -> bool {
 		self.requests.iter().filter(|x| x.get_response().is_none()).count() == 0
 	}
 
-	fn transform(&mut self) {
+	fn transform(&mut self) -> Result<(), String> {
 		let request = self.requests.iter_mut().filter(|x| x.get_response().is_none()).map(|x| x).nth(0);
-		match request {
+		let (result, can_start_over) = match request {
 			Some(r) => {
-				r.transform(&mut self.game)
+				(r.transform(&mut self.game), r.can_start_over())
 			},
 			None => panic!("There are no more requests!")
 		};
+		match result {
+			Ok(_) => Ok(()),
+			Err(e) if !can_start_over => Err(e),
+			Err(e) => {
+				self.game.restart();
+				if let Err(restart_error) = self.game.try_make_random_moves_and_end_on_random_side(DEFAULT_N_ROUNDS) {
+					return Err(format!("{} Couldn't start over from a new position: {}", e, restart_error));
+				}
+				self.emit(format!("{} Starting over from a new position.", e));
+				return Ok(());
+			}
+		}
 	}
 
 	fn validate(&mut self, input: String) -> Result<String, String> {
@@ -177,6 +202,7 @@ pub struct TrainerBuilder {
 	output: TrainerOutput,
 	blindfold: bool,
 	whites_perspective_only: bool,
+	xray: bool,
 	game: Game,
 }
 
@@ -187,6 +213,24 @@ impl TrainerBuilder {
 		return self;
 	}
 
+	pub fn with_fen(mut self, fen: &str) -> Result<Self, String> {
+		self.game = Game::from_fen(fen)?;
+		return Ok(self);
+	}
+
+	pub fn with_pgn(mut self, pgn: &str) -> Result<Self, String> {
+		self.game = match Game::all_from_pgn(pgn)?.into_iter().choose(&mut thread_rng()) {
+			Some(game) => game,
+			None => return Err("The PGN doesn't contain any games.".to_string())
+		};
+		return Ok(self);
+	}
+
+	pub fn with_chess960(mut self, n: usize) -> Result<Self, String> {
+		self.game = Game::chess960(n)?;
+		return Ok(self);
+	}
+
 	pub fn with_input_source(mut self, input_source: TrainerInputSource) -> Self {
 		self.input_source = input_source;
 		return self;
@@ -207,6 +251,11 @@ impl TrainerBuilder {
 		return self;
 	}
 
+	pub fn xray(mut self) -> Self {
+		self.xray = true;
+		return self;
+	}
+
 	pub fn build(self) -> Trainer {
 		Trainer {
 			requests: self.get_requests(self.mode),
@@ -221,6 +270,7 @@ impl TrainerBuilder {
 
 	fn get_requests(&self, mode: TrainerMode) -> Vec<TrainerRequest> {
 		let maybe_board = if self.blindfold { "".to_string() } else { "{board}".to_string() };
+		let maybe_xray = if self.xray { " (counting x-rays)" } else { "" };
 		match mode {
 			TrainerMode::Checks => {
 				vec![
@@ -303,6 +353,110 @@ impl TrainerBuilder {
 				}
 				return to_return;
 			},
+			TrainerMode::Forks => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the forks in this position: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
+						TrainerResponseValidator::ListOfMovesFromCurrentPosition,
+						TrainerResponseEvaluator::AreAllForksInPosition
+					)
+				]
+			},
+			TrainerMode::Discovered => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the discovered checks in this position: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
+						TrainerResponseValidator::ListOfMovesFromCurrentPosition,
+						TrainerResponseEvaluator::AreAllDiscoveredChecksInPosition
+					)
+				]
+			},
+			TrainerMode::Moves(piece, square) => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Give all of the legal moves for your {selected}: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndSelectPiece(piece, square),
+						TrainerResponseValidator::ListOfMovesFromCurrentPosition,
+						TrainerResponseEvaluator::AreAllLegalMovesOfSelectedPiece
+					)
+				]
+			},
+			TrainerMode::Hanging => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the pieces of either side that can be won in this position: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
+						TrainerResponseValidator::ListOfSquares,
+						TrainerResponseEvaluator::AreAllHangingPieces
+					)
+				]
+			},
+			TrainerMode::Mates => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the checkmates in this position: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesUntilMateIn(1),
+						TrainerResponseValidator::ListOfMovesFromCurrentPosition,
+						TrainerResponseEvaluator::AreAllMatesInPosition
+					)
+				]
+			},
+			TrainerMode::MateIn(n) => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						&format!("Find a forced mate in {}, giving the whole line including the replies: \n", n) +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesUntilMateIn(n),
+						TrainerResponseValidator::ListOfSequentialMoves,
+						TrainerResponseEvaluator::IsForcedMate(n)
+					)
+				]
+			},
+			TrainerMode::Threats => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the checks and winning captures your opponent would have if it were their move: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
+						TrainerResponseValidator::ListOfOpponentMovesFromCurrentPosition,
+						TrainerResponseEvaluator::AreAllThreatsInPosition
+					)
+				]
+			},
+			TrainerMode::Pins => {
+				vec![
+					TrainerRequest::new(
+						"You're playing the {side} pieces.\n".to_string() +
+						"Identify all of the pinned pieces of either side in this position: \n" +
+						"{moves}\n" +
+						&maybe_board,
+						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
+						TrainerResponseValidator::ListOfSquares,
+						TrainerResponseEvaluator::AreAllPinnedPieces
+					)
+				]
+			},
 			TrainerMode::MostDefended(target) => {
 				let target_string = target.to_plural_string();
 				let validator = match target {
@@ -311,12 +465,12 @@ impl TrainerBuilder {
 				};
 				vec![
 					TrainerRequest::new(
-						format!("Identify the top {} most defended {} for {{side}}: \n", DEFAULT_N_PIECES, target_string) +
+						format!("Identify the top {} most defended {} for {{side}}{}: \n", DEFAULT_N_PIECES, target_string, maybe_xray) +
 						&"{moves}\n".to_string() +
 						&maybe_board,
 						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
 						validator,
-						TrainerResponseEvaluator::AreNMostDefendedForNextToAct(DEFAULT_N_PIECES, target)
+						TrainerResponseEvaluator::AreNMostDefendedForNextToAct(DEFAULT_N_PIECES, target, self.xray)
 					)
 				]
 			},
@@ -328,12 +482,12 @@ impl TrainerBuilder {
 				};
 				vec![
 					TrainerRequest::new(
-						format!("Identify the top {} most attacked {} for {{side}}: \n", DEFAULT_N_PIECES, target_string) +
+						format!("Identify the top {} most attacked {} for {{side}}{}: \n", DEFAULT_N_PIECES, target_string, maybe_xray) +
 						&"{moves}\n".to_string() +
 						&maybe_board,
 						TrainerResponseTransformer::MakeRandomMovesAndEndOnRandomSide,
 						validator,
-						TrainerResponseEvaluator::AreNMostAttackedForNextToAct(DEFAULT_N_PIECES, target)
+						TrainerResponseEvaluator::AreNMostAttackedForNextToAct(DEFAULT_N_PIECES, target, self.xray)
 					)
 				]
 			},
@@ -382,10 +536,18 @@ pub enum TrainerMode {
 	Captures,
 	Sequential,
 	Position,
+	Pins,
+	Forks,
+	Mates,
+	MateIn(usize),
+	Threats,
+	Discovered,
+	Hanging,
 	MostDefended(Target),
 	MostAttacked(Target),
 	ShortestPath(Piece),
 	Color,
+	Moves(Option<Piece>, Option<Square>),
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -443,7 +605,8 @@ struct TrainerRequest {
 	transformer: TrainerResponseTransformer,
 	validator: TrainerResponseValidator,
 	evaluator: TrainerResponseEvaluator,
-	response: Option<String>
+	response: Option<String>,
+	selected: Option<(Square, Piece)>
 }
 
 impl TrainerRequest {
@@ -454,12 +617,16 @@ impl TrainerRequest {
 			transformer,
 			validator,
 			evaluator,
-			response: None
+			response: None,
+			selected: None
 		}
 	}
 
 	fn get_prompt(&self) -> String {
-		self.prompt.clone()
+		match self.selected {
+			Some((square, piece)) => self.prompt.replace("{selected}", &format!("{} on {}", piece.to_long_string().to_lowercase(), square.to_string())),
+			None => self.prompt.clone()
+		}
 	}
 
 	fn get_response(&self) -> Option<String> {
@@ -470,8 +637,13 @@ impl TrainerRequest {
 		self.response = Some(response);
 	}
 
-	fn transform(&mut self, game: &mut Game) {
-		self.transformer.transform(game)
+	fn transform(&mut self, game: &mut Game) -> Result<(), String> {
+		self.selected = self.transformer.transform(game)?;
+		return Ok(());
+	}
+
+	fn can_start_over(&self) -> bool {
+		self.transformer.can_start_over()
 	}
 
 	fn validate(&mut self, game: &Game, input: String) -> Result<String, String> {
@@ -491,7 +663,7 @@ impl TrainerRequest {
 	fn evaluate(&mut self, game: &Game) -> Result<String, String> {
 		match &self.response {
 			Some(response) => {
-				return self.evaluator.evaluate(game, response.to_string());
+				return self.evaluator.evaluate(game, response.to_string(), self.selected);
 			},
 			None => panic!("Evaluate called without a validated response!")
 		}
@@ -505,26 +677,31 @@ enum TrainerResponseTransformer {
 	MakeRandomMove,
 	MakeRandomMoves(usize),
 	MakeRandomMovesAndEndOnRandomSide,
+	MakeRandomMovesUntilMateIn(usize),
 	PlacePieceAtSquare(Piece, Square),
 	PlacePieceAtRandomSquare(Piece),
+	MakeRandomMovesAndSelectPiece(Option<Piece>, Option<Square>),
 }
 
 impl TrainerResponseTransformer {
 
-	fn transform(&self, game: &mut Game) {
+	fn transform(&self, game: &mut Game) -> Result<Option<(Square, Piece)>, String> {
 		match self {
 			Self::DoNothing => {},
 			Self::MakeRandomMove => {
-				game.make_random_move();
+				game.try_make_random_move()?;
 			},
 			Self::MakeRandomMoves(n) => {
-				game.make_random_moves(*n);
+				game.try_make_random_moves(*n)?;
 			},
 			Self::MakeRandomMovesAndEndOnRandomSide => {
-				if game.get_moves().len() == 0 {
-					game.make_random_moves_and_end_on_random_side(DEFAULT_N_ROUNDS);
+				if game.is_starting_position() {
+					game.try_make_random_moves_and_end_on_random_side(DEFAULT_N_ROUNDS)?;
 				}
 			},
+			Self::MakeRandomMovesUntilMateIn(n) => {
+				game.try_make_random_moves_until_mate_in(*n)?;
+			},
 			Self::PlacePieceAtSquare(piece, square) => {
 				game.clear_board();
 				game.add_piece(Side::White, *piece, *square);
@@ -533,8 +710,16 @@ impl TrainerResponseTransformer {
 				let square = Square::get_random();
 				game.clear_board();
 				game.add_piece(Side::White, *piece, square);
+			},
+			Self::MakeRandomMovesAndSelectPiece(piece, square) => {
+				return game.try_make_random_moves_until_piece(DEFAULT_N_ROUNDS, *piece, *square).map(Some);
 			}
-		}
+		};
+		return Ok(None);
+	}
+
+	fn can_start_over(&self) -> bool {
+		return !matches!(self, Self::MakeRandomMovesAndSelectPiece(_, _));
 	}
 
 }
@@ -547,6 +732,7 @@ enum TrainerResponseValidator {
 	ListOfSequentialMoves,
 	ListOfSequentialMovesForCurrentSide,
 	ListOfMovesFromCurrentPosition,
+	ListOfOpponentMovesFromCurrentPosition,
 	ListOfPiecesForNextToAct,
 }
 
@@ -596,6 +782,15 @@ impl TrainerResponseValidator {
 					Err(e) => Trainer::get_error(e)
 				}
 			},
+			Self::ListOfOpponentMovesFromCurrentPosition => {
+				if input.clone().to_lowercase() == "none" {
+					return Ok(format!("{} is an empty list of moves!", input.clone()));
+				}
+				match game.parse_opponent_moves_from_current_position(input.clone()) {
+					Ok(_) => Ok(format!("{} is a valid list of opponent moves from current position!", input.clone())),
+					Err(e) => Trainer::get_error(e)
+				}
+			},
 			Self::ListOfPiecesForNextToAct => {
 				if input.clone().to_lowercase() == "none" {
 					return Ok(format!("{} is an empty list of pieces!", input.clone()));
@@ -615,15 +810,23 @@ enum TrainerResponseEvaluator {
 	IsSquareColor(SquareColor),
 	AreAllChecksInPosition,
 	AreAllCapturesInPosition,
+	AreAllForksInPosition,
+	AreAllMatesInPosition,
+	AreAllThreatsInPosition,
+	AreAllDiscoveredChecksInPosition,
+	AreAllLegalMovesOfSelectedPiece,
+	IsForcedMate(usize),
 	AreAllPiecePositions(Piece),
-	AreNMostDefendedForNextToAct(usize, Target),
-	AreNMostAttackedForNextToAct(usize, Target),
+	AreAllPinnedPieces,
+	AreAllHangingPieces,
+	AreNMostDefendedForNextToAct(usize, Target, bool),
+	AreNMostAttackedForNextToAct(usize, Target, bool),
 	IsShortestPath(Piece, Square, Square),
 }
 
 impl TrainerResponseEvaluator {
 
-	fn evaluate(&self, game: &Game, response: String) -> Result<String, String> {
+	fn evaluate(&self, game: &Game, response: String, selected: Option<(Square, Piece)>) -> Result<String, String> {
 		match self {
 			Self::IsSquareColor(expected) => {
 				match SquareColor::try_parse(response.clone()) {
@@ -661,6 +864,85 @@ impl TrainerResponseEvaluator {
 					}
 				};
 			},
+			Self::AreAllForksInPosition => {
+				let potential_forks_result = Self::parse_moves_from_current_position(game, response);
+				match potential_forks_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(forks) => {
+						let potential_forks: HashSet<Move> = forks.into_iter().collect();
+						let actual_forks: HashSet<Move> = game.get_forking_moves().into_iter().collect();
+						return Self::compare_move_sets(game, potential_forks, actual_forks, "forks".to_string());
+					}
+				};
+			},
+			Self::AreAllDiscoveredChecksInPosition => {
+				let potential_checks_result = Self::parse_moves_from_current_position(game, response);
+				match potential_checks_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(checks) => {
+						let potential_checks: HashSet<Move> = checks.into_iter().collect();
+						let actual_checks: HashSet<Move> = game.get_discovered_checks().into_iter().collect();
+						let missing: Vec<Move> = actual_checks.difference(&potential_checks).copied().collect();
+						if potential_checks.is_subset(&actual_checks) && !missing.is_empty() {
+							let descriptions: Vec<String> = missing.into_iter().map(|m| Self::describe_discovered_check(game, m)).collect();
+							return Trainer::get_error(format!("Incorrect!  You missed the following discovered checks: {}", descriptions.join(", ")));
+						}
+						return Self::compare_move_sets(game, potential_checks, actual_checks, "discovered checks".to_string());
+					}
+				};
+			},
+			Self::AreAllLegalMovesOfSelectedPiece => {
+				let (square, piece) = match selected {
+					Some(x) => x,
+					None => return Trainer::get_error("No piece was selected!".to_string())
+				};
+				match Self::parse_moves_from_current_position(game, response) {
+					Err(e) => return Trainer::get_error(e),
+					Ok(moves) => {
+						let potential_moves: HashSet<Move> = moves.into_iter().collect();
+						let actual_moves: HashSet<Move> = game.get_legal_moves(square).into_iter().collect();
+						return Self::compare_move_sets(game, potential_moves, actual_moves, format!("legal moves for the {} on {}", piece.to_long_string().to_lowercase(), square.to_string()));
+					}
+				};
+			},
+			Self::AreAllMatesInPosition => {
+				let potential_mates_result = Self::parse_moves_from_current_position(game, response);
+				match potential_mates_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(mates) => {
+						let potential_mates: HashSet<Move> = mates.into_iter().collect();
+						let actual_mates: HashSet<Move> = game.get_mating_moves().into_iter().collect();
+						return Self::compare_move_sets(game, potential_mates, actual_mates, "checkmates".to_string());
+					}
+				};
+			},
+			Self::IsForcedMate(n) => {
+				let line = match game.parse_sequential_moves(response) {
+					Ok(line) => line,
+					Err(e) => return Trainer::get_error(e)
+				};
+				match game.get_board_clone().validate_forced_mate_line(game.get_next_to_act(), *n, line) {
+					Ok(_) => return Trainer::get_success("Correct!".to_string()),
+					Err(e) => {
+						let answers = game.get_board_clone().get_forced_mate_moves(game.get_next_to_act(), *n);
+						return Trainer::get_error(format!("Incorrect!  {}  Mate starts with one of: {}", e, game.get_move_strings_from_current_position(answers)));
+					}
+				}
+			},
+			Self::AreAllThreatsInPosition => {
+				let potential_threats_result = match response.to_lowercase().as_str() {
+					"none" => Ok(Vec::new()),
+					_ => game.parse_opponent_moves_from_current_position(response)
+				};
+				match potential_threats_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(threats) => {
+						let potential_threats: HashSet<Move> = threats.into_iter().collect();
+						let actual_threats: HashSet<Move> = game.get_threats().into_iter().collect();
+						return Self::compare_move_sets(game, potential_threats, actual_threats, "threats".to_string());
+					}
+				};
+			},
 			Self::AreAllPiecePositions(piece) => {
 				let potential_positions_result = Self::parse_squares(response);
 				match potential_positions_result {
@@ -672,11 +954,33 @@ impl TrainerResponseEvaluator {
 					}
 				}
 			},
-			Self::AreNMostDefendedForNextToAct(n, target) => {
-				return Self::evaluate_most_defended_or_attacked(game, *n, *target, true, response);
+			Self::AreAllPinnedPieces => {
+				let potential_pins_result = Self::parse_squares(response);
+				match potential_pins_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(pins) => {
+						let potential_pins: HashSet<Square> = pins.into_iter().collect();
+						let actual_pins: HashSet<Square> = game.get_pinned_pieces().into_iter().collect();
+						return Self::compare_square_sets(potential_pins, actual_pins, "pinned pieces".to_string());
+					}
+				}
 			},
-			Self::AreNMostAttackedForNextToAct(n, target) => {
-				return Self::evaluate_most_defended_or_attacked(game, *n, *target, false, response);
+			Self::AreAllHangingPieces => {
+				let potential_hanging_result = Self::parse_squares(response);
+				match potential_hanging_result {
+					Err(e) => return Trainer::get_error(e),
+					Ok(hanging) => {
+						let potential_hanging: HashSet<Square> = hanging.into_iter().collect();
+						let actual_hanging: HashSet<Square> = game.get_hanging_pieces().into_iter().collect();
+						return Self::compare_square_sets(potential_hanging, actual_hanging, "hanging pieces".to_string());
+					}
+				}
+			},
+			Self::AreNMostDefendedForNextToAct(n, target, xray) => {
+				return Self::evaluate_most_defended_or_attacked(game, *n, *target, true, *xray, response);
+			},
+			Self::AreNMostAttackedForNextToAct(n, target, xray) => {
+				return Self::evaluate_most_defended_or_attacked(game, *n, *target, false, *xray, response);
 			},
 			Self::IsShortestPath(piece, starting_square, ending_square) => {
 				let shortest_paths = piece.get_shortest_paths(*starting_square, *ending_square);
@@ -692,17 +996,18 @@ impl TrainerResponseEvaluator {
 		}
 	}
 
-	fn evaluate_most_defended_or_attacked(game: &Game, n: usize, target: Target, defended: bool, response: String) -> Result<String, String> {
+	fn evaluate_most_defended_or_attacked(game: &Game, n: usize, target: Target, defended: bool, xray: bool, response: String) -> Result<String, String> {
 		let potential_squares_result = Self::parse_squares(response);
 		match potential_squares_result {
 			Err(e) => return Trainer::get_error(e),
 			Ok(squares) => {
 				let potential_squares: HashSet<Square> = squares.into_iter().collect();
 
-				let most_defended_or_attacked_squares = if defended {
-					game.get_most_defended_squares(game.get_next_to_act())
-				} else {
-					game.get_most_attacked_squares(game.get_next_to_act())
+				let most_defended_or_attacked_squares = match (defended, xray) {
+					(true, false) => game.get_most_defended_squares(game.get_next_to_act()),
+					(false, false) => game.get_most_attacked_squares(game.get_next_to_act()),
+					(true, true) => game.get_most_xray_defended_squares(game.get_next_to_act()),
+					(false, true) => game.get_most_xray_attacked_squares(game.get_next_to_act()),
 				};
 
 				let most_defended_or_attacked = match target {
@@ -774,6 +1079,13 @@ impl TrainerResponseEvaluator {
 		return Trainer::get_success("Correct!".to_string());
 	}
 
+	fn describe_discovered_check(game: &Game, m: Move) -> String {
+		let unmasked: Vec<String> = game.get_unmasked_checkers(m).into_iter()
+			.map(|(square, piece)| format!("{} on {}", piece.to_long_string().to_lowercase(), square.to_string()))
+			.collect();
+		return format!("{} (unmasking the {})", game.get_move_string_from_current_position(m), unmasked.join(" and the "));
+	}
+
 	fn compare_square_sets(potential: HashSet<Square>, actual: HashSet<Square>, plural_name: String) -> Result<String, String> {
 		let non: HashSet<Square> = potential.difference(&actual).map(|x| *x).collect();
 		if non.len() > 0 {
@@ -858,6 +1170,130 @@ mod tests {
 		
 	}
 
+	#[test]
+	fn trainer_runs_from_fen() {
+		let buffer = TrainerInputSource::Buffer(vec!["Qxf7, Bxf7, Qxe5".to_string()]);
+		let mut trainer = Trainer::builder(TrainerMode::Checks)
+			.with_input_source(buffer)
+			.with_buffer_output()
+			.with_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+			.unwrap()
+			.build();
+		trainer.run();
+
+		assert_eq!(trainer.game.get_moves().len(), 0);
+		match trainer.get_output() {
+			TrainerOutput::Buffer(buffer) => assert_eq!(buffer[buffer.len() - 1], Color::Green.format("Correct!".to_string())),
+			_ => panic!("Should have been a buffer.")
+		};
+
+		assert!(Trainer::builder(TrainerMode::Checks).with_fen("8/8/8 w - - 0 1").is_err());
+	}
+
+	#[test]
+	fn trainer_survives_finished_games() {
+		let buffer = TrainerInputSource::Buffer(vec!["none".to_string()]);
+		let mut trainer = Trainer::builder(TrainerMode::Sequential)
+			.with_input_source(buffer)
+			.with_buffer_output()
+			.with_fen("7k/8/6K1/8/8/8/8/3Q4 w - - 0 1")
+			.unwrap()
+			.build();
+		trainer.game.make_moves_from_string("Qd8".to_string());
+		assert!(trainer.game.is_game_over());
+		trainer.run();
+
+		assert_eq!(trainer.get_state(), TrainerState::Finished);
+		match trainer.get_output() {
+			TrainerOutput::Buffer(buffer) => {
+				let restart = buffer.iter().position(|s| s.contains("Starting over from a new position.")).unwrap();
+				assert_eq!(restart, 0);
+				let prompt = &buffer[restart + 1];
+				assert!(prompt.contains("Identify all of the checks in this position"));
+				assert!(!prompt.contains("Qd8#"));
+			},
+			_ => panic!("Should have been a buffer.")
+		};
+
+		let buffer = TrainerInputSource::Buffer(vec!["none".to_string()]);
+		let mut trainer = Trainer::builder(TrainerMode::Sequential)
+			.with_input_source(buffer)
+			.with_buffer_output()
+			.with_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+			.unwrap()
+			.build();
+		trainer.run();
+
+		assert_eq!(trainer.get_state(), TrainerState::Finished);
+		match trainer.get_output() {
+			TrainerOutput::Buffer(buffer) => {
+				assert_eq!(buffer.len(), 1);
+				assert!(buffer[0].contains("Couldn't start over from a new position"));
+			},
+			_ => panic!("Should have been a buffer.")
+		};
+	}
+
+	fn run_mode_output(mode: TrainerMode, fen: &str, input: &str) -> Vec<String> {
+		let buffer = TrainerInputSource::Buffer(vec![input.to_string()]);
+		let mut trainer = Trainer::builder(mode)
+			.with_input_source(buffer)
+			.with_buffer_output()
+			.with_fen(fen)
+			.unwrap()
+			.build();
+		trainer.run();
+
+		assert_eq!(trainer.get_state(), TrainerState::Finished);
+		match trainer.get_output() {
+			TrainerOutput::Buffer(buffer) => return buffer,
+			_ => panic!("Should have been a buffer.")
+		};
+	}
+
+	fn run_mode(mode: TrainerMode, fen: &str, input: &str) -> String {
+		return run_mode_output(mode, fen, input).pop().unwrap();
+	}
+
+	#[test]
+	fn trainer_evaluates_discovered_checks() {
+		let fen = "4k3/8/8/8/4N3/3B4/8/4RK2 w - - 0 1";
+		let evaluation = run_mode(TrainerMode::Discovered, fen, "Nc3, Nc5, Nd2, Nf2, Ng3, Ng5, Nd6");
+		assert!(evaluation.contains("You missed the following discovered checks: Nf6"));
+		assert!(evaluation.contains("(unmasking the rook on e1)"));
+
+		let evaluation = run_mode(TrainerMode::Discovered, fen, "Nc3, Bb5");
+		assert!(evaluation.contains("The following are not discovered checks: Bb5"));
+	}
+
+	#[test]
+	fn trainer_evaluates_legal_moves() {
+		let fen = "4k3/8/8/8/4N3/8/8/4K3 w - - 0 1";
+		let output = run_mode_output(TrainerMode::Moves(None, Some(Square::from_string("e4"))), fen, "Nc3, Nc5, Nd2, Nd6, Nf2, Nf6, Ng3, Ng5");
+		assert!(output[0].contains("Give all of the legal moves for your knight on e4"));
+		assert_eq!(output[output.len() - 1], Color::Green.format("Correct!".to_string()));
+
+		let evaluation = run_mode(TrainerMode::Moves(Some(Piece::Knight), None), fen, "Nc3, Nc5");
+		assert!(evaluation.contains("You missed the following legal moves for the knight on e4"));
+
+		let evaluation = run_mode(TrainerMode::Moves(Some(Piece::Queen), None), fen, "");
+		assert!(evaluation.contains("has no queen to move"));
+	}
+
+	#[test]
+	fn trainer_evaluates_pins() {
+		let fen = "4k3/4r3/7b/1b6/B4N2/3N4/3QN3/4K3 w - - 0 1";
+		assert_eq!(run_mode(TrainerMode::Pins, fen, "e2, f4, b5"), Color::Green.format("Correct!".to_string()));
+		assert!(run_mode(TrainerMode::Pins, fen, "e2, d3").contains("The following are not pinned pieces: d3"));
+	}
+
+	#[test]
+	fn trainer_evaluates_forced_mates() {
+		let fen = "7k/8/8/8/8/8/R7/1R4K1 w - - 0 1";
+		assert_eq!(run_mode(TrainerMode::MateIn(2), fen, "Ra7, Kg8, Rb8"), Color::Green.format("Correct!".to_string()));
+		assert!(run_mode(TrainerMode::MateIn(2), fen, "Ra8+, Kg7, Rb7+").contains("Ra8+ doesn't force mate in 2."));
+	}
+
 	#[test]
 	fn test_builder_builds() {
 		let moves = "